@@ -5,7 +5,9 @@ use linux_embedded_hal::Delay;
 use linux_embedded_hal::{Pin, Spidev};
 
 extern crate ssd1675;
-use ssd1675::{Builder, Color, Dimensions, Display, GraphicDisplay, Rotation};
+use ssd1675::waveform::Phase;
+use ssd1675::waveform::Voltage::{Vsh1, Vsh2, Vsl, Vss};
+use ssd1675::{Builder, Color, Dimensions, Display, GraphicDisplay, Rotation, SleepMode, Waveform};
 
 // Graphics
 #[macro_use]
@@ -30,26 +32,65 @@ use std::{fs, io};
 const ROWS: u16 = 212;
 const COLS: u8 = 104;
 
-#[rustfmt::skip]
-const LUT: [u8; 70] = [
-    // Phase 0     Phase 1     Phase 2     Phase 3     Phase 4     Phase 5     Phase 6
-    // A B C D     A B C D     A B C D     A B C D     A B C D     A B C D     A B C D
-    0b01001000, 0b10100000, 0b00010000, 0b00010000, 0b00010011, 0b00000000, 0b00000000,  // LUT0 - Black
-    0b01001000, 0b10100000, 0b10000000, 0b00000000, 0b00000011, 0b00000000, 0b00000000,  // LUTT1 - White
-    0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,  // IGNORE
-    0b01001000, 0b10100101, 0b00000000, 0b10111011, 0b00000000, 0b00000000, 0b00000000,  // LUT3 - Red
-    0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,  // LUT4 - VCOM
-
-    // Duration            |  Repeat
-    // A   B     C     D   |
-    64,   12,   32,   12,    6,   // 0 Flash
-    16,   8,    4,    4,     6,   // 1 clear
-    4,    8,    8,    16,    16,  // 2 bring in the black
-    2,    2,    2,    64,    32,  // 3 time for red
-    2,    2,    2,    2,     2,   // 4 final black sharpen phase
-    0,    0,    0,    0,     0,   // 5
-    0,    0,    0,    0,     0    // 6
-];
+const WAVEFORM: Waveform = Waveform::new()
+    // Flash
+    .phase(
+        0,
+        Phase {
+            black: [Vsh1, Vss, Vsl, Vss],
+            white: [Vsh1, Vss, Vsl, Vss],
+            red: [Vsh1, Vss, Vsl, Vss],
+            durations: [64, 12, 32, 12],
+            repeat: 6,
+            ..Phase::EMPTY
+        },
+    )
+    // Clear
+    .phase(
+        1,
+        Phase {
+            black: [Vsl, Vsl, Vss, Vss],
+            white: [Vsl, Vsl, Vss, Vss],
+            red: [Vsl, Vsl, Vsh1, Vsh1],
+            durations: [16, 8, 4, 4],
+            repeat: 6,
+            ..Phase::EMPTY
+        },
+    )
+    // Bring in the black
+    .phase(
+        2,
+        Phase {
+            black: [Vss, Vsh1, Vss, Vss],
+            white: [Vsl, Vss, Vss, Vss],
+            durations: [4, 8, 8, 16],
+            repeat: 16,
+            ..Phase::EMPTY
+        },
+    )
+    // Time for red
+    .phase(
+        3,
+        Phase {
+            black: [Vss, Vsh1, Vss, Vss],
+            red: [Vsl, Vsh2, Vsl, Vsh2],
+            durations: [2, 2, 2, 64],
+            repeat: 32,
+            ..Phase::EMPTY
+        },
+    )
+    // Final black sharpen phase
+    .phase(
+        4,
+        Phase {
+            black: [Vss, Vsh1, Vss, Vsh2],
+            white: [Vss, Vss, Vss, Vsh2],
+            durations: [2, 2, 2, 2],
+            repeat: 2,
+            ..Phase::EMPTY
+        },
+    );
+const LUT: [u8; 70] = WAVEFORM.to_bytes();
 
 fn main() -> Result<(), std::io::Error> {
     // Configure SPI
@@ -103,16 +144,19 @@ fn main() -> Result<(), std::io::Error> {
         })
         .rotation(Rotation::Rotate270)
         .lut(&LUT)
+        // The waveform takes about 39 seconds (see `Config::refresh_duration`), longer than the
+        // default timeout
+        .busy_timeout_ms(60_000)
         .build()
         .expect("invalid configuration");
     let display = Display::new(controller, config);
     let mut display = GraphicDisplay::new(display, &mut black_buffer, &mut red_buffer);
+    display.reset(&mut delay).expect("error resetting display");
+    println!("Reset and initialised");
 
     // Main loop. Displays CPU temperature, uname, and uptime every minute with a red Raspberry Pi
     // header.
     loop {
-        display.reset(&mut delay).expect("error resetting display");
-        println!("Reset and initialised");
         let one_minute = Duration::from_secs(60);
 
         display.clear(Color::White);
@@ -167,9 +211,13 @@ fn main() -> Result<(), std::io::Error> {
         println!("Update...");
 
         println!("Finished - going to sleep");
-        display.deep_sleep()?;
+        let sleeping = display
+            .deep_sleep(SleepMode::PreserveRAM, &mut delay)
+            .expect("error entering deep sleep");
 
         sleep(one_minute);
+        display = sleeping.wake(&mut delay).expect("error waking display");
+        println!("Woken and initialised");
     }
 }
 
@@ -0,0 +1,204 @@
+//! Async support built on embedded-hal-async.
+//!
+//! [AsyncInterface] is built on the embedded-hal-async `SpiDevice` and `Wait` traits. Rather than
+//! polling the BUSY pin it awaits BUSY going low, so other tasks can run while the panel
+//! refreshes. The async methods of [Display](../display/struct.Display.html) and
+//! [GraphicDisplay](../graphics/struct.GraphicDisplay.html) are suffixed with `_async`, e.g.
+//! `update_async`.
+//!
+//! There is no timeout when waiting for BUSY. If one is needed wrap the future with the timeout
+//! facility of the executor, such as `embassy_time::with_timeout`.
+//!
+//! ### Example
+//!
+//! ```ignore
+//! use ssd1675::asynch::AsyncInterface;
+//! use ssd1675::{Builder, Dimensions, Display, Rotation};
+//!
+//! // `spi` implements `SpiDevice`, `busy` `Wait`, the other pins `OutputPin`, and `delay`
+//! // `DelayNs`, all from embedded-hal-async or embedded-hal 1.0
+//! let controller = AsyncInterface::new(spi, busy, dc, reset);
+//! let config = Builder::new()
+//!     .dimensions(Dimensions { rows: 212, cols: 104 })
+//!     .rotation(Rotation::Rotate270)
+//!     .build()
+//!     .expect("invalid configuration");
+//! let mut display = Display::new(controller, config);
+//! display.reset_async(&mut delay).await.expect("error resetting display");
+//! display.update_async(&black, &red, &mut delay).await.expect("error updating display");
+//! ```
+//!
+//! [AsyncInterface]: struct.AsyncInterface.html
+
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::spi::SpiDevice;
+
+use crate::error::Error;
+use crate::interface::RESET_DELAY_MS;
+
+/// Trait implemented by displays to provide an async implementation of core functionality.
+///
+/// This is the async counterpart of [DisplayInterface](../interface/trait.DisplayInterface.html).
+#[allow(async_fn_in_trait)]
+pub trait AsyncDisplayInterface {
+    /// Error type of the serial interface.
+    type SpiError;
+    /// Error type of the GPIO pins.
+    type PinError;
+
+    /// Send a command to the controller.
+    ///
+    /// Prefer calling `execute_async` on a [Commmand](../command/enum.Command.html) over calling
+    /// this directly.
+    async fn send_command(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>>;
+
+    /// Send data for a command.
+    async fn send_data(&mut self, data: &[u8])
+        -> Result<(), Error<Self::SpiError, Self::PinError>>;
+
+    /// Reset the controller.
+    async fn reset<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>>;
+
+    /// Wait for the controller to indicate it is not busy.
+    async fn busy_wait(&mut self) -> Result<(), Error<Self::SpiError, Self::PinError>>;
+}
+
+impl<I> AsyncDisplayInterface for &mut I
+where
+    I: AsyncDisplayInterface,
+{
+    type SpiError = I::SpiError;
+    type PinError = I::PinError;
+
+    async fn send_command(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).send_command(command).await
+    }
+
+    async fn send_data(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).send_data(data).await
+    }
+
+    async fn reset<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).reset(delay).await
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).busy_wait().await
+    }
+}
+
+/// The async hardware interface to a display.
+///
+/// The `SpiDevice` manages chip select. The BUSY, DC, and RESET pins must share the same error
+/// type.
+pub struct AsyncInterface<SPI, BUSY, DC, RESET> {
+    /// SPI device
+    spi: SPI,
+    /// Active low busy pin (input)
+    busy: BUSY,
+    /// Data/Command Control Pin (High for data, Low for command) (output)
+    dc: DC,
+    /// Pin for reseting the controller (output)
+    reset: RESET,
+}
+
+impl<SPI, BUSY, DC, RESET> AsyncInterface<SPI, BUSY, DC, RESET>
+where
+    SPI: SpiDevice,
+    BUSY: Wait,
+    DC: OutputPin,
+    RESET: OutputPin,
+{
+    /// Create a new AsyncInterface from embedded-hal-async traits.
+    pub fn new(spi: SPI, busy: BUSY, dc: DC, reset: RESET) -> Self {
+        Self {
+            spi,
+            busy,
+            dc,
+            reset,
+        }
+    }
+
+    /// Consume the interface, returning the SPI device and pins.
+    pub fn release(self) -> (SPI, BUSY, DC, RESET) {
+        (self.spi, self.busy, self.dc, self.reset)
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
+        // Linux has a default limit of 4096 bytes per SPI transfer
+        // https://github.com/torvalds/linux/blob/ccda4af0f4b92f7b4c308d3acc262f4a7e3affad/drivers/spi/spidev.c#L93
+        if cfg!(target_os = "linux") {
+            for data_chunk in data.chunks(4096) {
+                self.spi.write(data_chunk).await?;
+            }
+        } else {
+            self.spi.write(data).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<SPI, BUSY, DC, RESET, PinE> AsyncDisplayInterface for AsyncInterface<SPI, BUSY, DC, RESET>
+where
+    SPI: SpiDevice,
+    BUSY: Wait<Error = PinE>,
+    DC: OutputPin<Error = PinE>,
+    RESET: OutputPin<Error = PinE>,
+{
+    type SpiError = SPI::Error;
+    type PinError = PinE;
+
+    async fn reset<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.reset.set_low().map_err(Error::ResetPin)?;
+        delay.delay_ms(u32::from(RESET_DELAY_MS)).await;
+        self.reset.set_high().map_err(Error::ResetPin)?;
+        delay.delay_ms(u32::from(RESET_DELAY_MS)).await;
+
+        Ok(())
+    }
+
+    async fn send_command(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_low().map_err(Error::DcPin)?;
+        self.write(&[command]).await.map_err(Error::Spi)?;
+        self.dc.set_high().map_err(Error::DcPin)?;
+
+        Ok(())
+    }
+
+    async fn send_data(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_high().map_err(Error::DcPin)?;
+        self.write(data).await.map_err(Error::Spi)
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        // Resolves immediately if BUSY is already low, otherwise on its falling edge
+        self.busy.wait_for_low().await.map_err(Error::BusyPin)
+    }
+}
@@ -40,7 +40,7 @@ mod tests {
 
     #[test]
     fn from_u8_panic() {
-        for val in 3..=u8::max_value() {
+        for val in 3..=u8::MAX {
             extern crate std;
             let result = std::panic::catch_unwind(|| Color::from(val));
             assert!(result.is_err());
@@ -1,8 +1,15 @@
+#[cfg(feature = "async")]
+use crate::asynch::AsyncDisplayInterface;
+use crate::error::Error;
+use crate::interface::{DisplayInterface, ReadInterface};
+use crate::waveform::Voltage;
 use core;
-use interface::DisplayInterface;
 
 const MAX_GATES: u16 = 296;
 const MAX_DUMMY_LINE_PERIOD: u8 = 127;
+const MAX_VCOM_SENSE_DURATION: u8 = 15;
+const MAX_VCI_LEVEL_THRESHOLD: u8 = 7;
+const MAX_TEMPERATURE_REGISTER: u16 = 0xFFF;
 
 trait Contains<C>
 where
@@ -14,7 +21,7 @@ where
 /// The address increment orientation when writing image data. This configures how the controller
 /// will auto-increment the row and column addresses when image data is written using the
 /// `WriteImageData` command.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum IncrementAxis {
     /// X direction
     Horizontal,
@@ -22,7 +29,7 @@ pub enum IncrementAxis {
     Vertical,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DataEntryMode {
     DecrementXDecrementY,
     IncrementXDecrementY,
@@ -30,20 +37,31 @@ pub enum DataEntryMode {
     IncrementYIncrementX, // POR
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TemperatureSensor {
     Internal,
     External,
 }
 
-#[derive(Clone, Copy)]
+/// How the contents of a RAM are used when the display is updated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RamOption {
+    /// Use the RAM as written (POR)
     Normal,
+    /// Ignore the RAM, treating every bit as 0
     Bypass,
+    /// Invert every bit of the RAM
     Invert,
 }
 
-#[derive(Clone, Copy)]
+/// Selects which RAM is read by the `ReadData` command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ram {
+    BlackWhite,
+    Red,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DeepSleepMode {
     /// Not sleeping
     Normal,
@@ -53,8 +71,132 @@ pub enum DeepSleepMode {
     DiscardRAM,
 }
 
+/// Selects one of LUT0 to LUT3 for the border to follow.
+///
+/// See the fields of [Phase](../waveform/struct.Phase.html) for the pixels each LUT drives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderLut {
+    Lut0,
+    Lut1,
+    Lut2,
+    Lut3,
+}
+
+/// The waveform driven on the border of the panel (VBD).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderWaveform {
+    /// Leave the border floating (POR)
+    HiZ,
+    /// Hold the border at a fixed level
+    FixLevel(Voltage),
+    /// Drive the border with VCOM
+    Vcom,
+    /// Follow the waveform of a LUT, so the border transitions like pixels driven by it
+    FollowLut(BorderLut),
+    /// Follow the waveform of a LUT, but output VCOM while driving red
+    GsTransition(BorderLut),
+}
+
+/// The display mode used by the display step of an `UpdateSequence`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// Drive the panel with the LUT for a full update
+    Mode1,
+    /// Drive the panel with the LUT for a fast update
+    Mode2,
+}
+
+/// The steps run when the display update sequence is activated with `UpdateDisplay`.
+///
+/// Steps run in the order of the builder methods below, whatever order they're called in. A
+/// sequence that enables the clock or analog block without disabling it leaves it enabled, which
+/// saves powering up again for back-to-back updates.
+///
+/// ### Example
+///
+/// ```
+/// use ssd1675::command::{DisplayMode, UpdateSequence};
+///
+/// const SEQUENCE: UpdateSequence = UpdateSequence::new()
+///     .enable_clock()
+///     .enable_analog()
+///     .display(DisplayMode::Mode1)
+///     .disable_analog()
+///     .disable_clock();
+///
+/// assert_eq!(SEQUENCE, UpdateSequence::FULL);
+/// assert_eq!(SEQUENCE.bits(), 0xC7);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UpdateSequence(u8);
+
+impl UpdateSequence {
+    /// Power up, display with display mode 1, then power down. Used for full updates.
+    pub const FULL: UpdateSequence = UpdateSequence(0xC7);
+    /// Power up, display with display mode 2, then power down. Used for fast updates.
+    pub const FAST: UpdateSequence = UpdateSequence(0xCF);
+
+    /// Create a sequence with no steps.
+    pub const fn new() -> Self {
+        UpdateSequence(0)
+    }
+
+    /// Create a sequence from the value of the display update control 2 register.
+    pub const fn from_bits(bits: u8) -> Self {
+        UpdateSequence(bits)
+    }
+
+    /// The value of the display update control 2 register.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Enable the clock signal.
+    pub const fn enable_clock(self) -> Self {
+        UpdateSequence(self.0 | 0x80)
+    }
+
+    /// Enable the analog block, which powers the booster and drivers.
+    pub const fn enable_analog(self) -> Self {
+        UpdateSequence(self.0 | 0x40)
+    }
+
+    /// Load the temperature from the selected sensor.
+    pub const fn load_temperature(self) -> Self {
+        UpdateSequence(self.0 | 0x20)
+    }
+
+    /// Load the LUT for the temperature from OTP, replacing any written with `WriteLUT`.
+    pub const fn load_lut(self) -> Self {
+        UpdateSequence(self.0 | 0x10)
+    }
+
+    /// Whether the sequence loads the LUT from OTP.
+    pub(crate) const fn loads_lut(self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    /// Drive the panel from RAM using `mode`.
+    pub const fn display(self, mode: DisplayMode) -> Self {
+        match mode {
+            DisplayMode::Mode1 => UpdateSequence(self.0 & !0x08 | 0x04),
+            DisplayMode::Mode2 => UpdateSequence(self.0 | 0x0C),
+        }
+    }
+
+    /// Disable the analog block.
+    pub const fn disable_analog(self) -> Self {
+        UpdateSequence(self.0 | 0x02)
+    }
+
+    /// Disable the clock signal.
+    pub const fn disable_clock(self) -> Self {
+        UpdateSequence(self.0 | 0x01)
+    }
+}
+
 /// A command that can be issued to the controller.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
     /// Set the MUX of gate lines, scanning sequence and direction
     /// 0: MAX gate lines
@@ -82,19 +224,25 @@ pub enum Command {
     /// Perform a soft reset, and reset all parameters to their default values
     /// BUSY will be high when in progress.
     SoftReset,
-    // /// Start HV ready detection. Read result with `ReadStatusBit` command
-    // StartHVReadyDetection,
-    // /// Start VCI level detection
-    // /// 0: threshold
-    // /// Read result with `ReadStatusBit` command
-    // StartVCILevelDetection(u8),
+    /// Start HV ready detection. Read result with `StatusBitRead` command
+    /// BUSY will be high when in progress.
+    StartHVReadyDetection,
+    /// Start VCI level detection
+    /// 0: threshold
+    /// Read result with `StatusBitRead` command
+    /// BUSY will be high when in progress.
+    StartVCILevelDetection(u8),
     /// Specify internal or external temperature sensor
     TemperatatSensorSelection(TemperatureSensor),
-    /// Write to the temperature sensor register
+    /// Write to the temperature sensor register.
+    /// The 12-bit value is in units of 1/16 degree Celsius, two's complement
     WriteTemperatureSensor(u16),
-    /// Read from the temperature sensor register
-    ReadTemperatureSensor(u16),
+    /// Read from the temperature sensor register (2 bytes)
+    ReadTemperatureSensor,
     /// Write a command to the external temperature sensor
+    /// 0: Number of bytes to send and pointer/command setting
+    /// 1: Command 1
+    /// 2: Command 2
     WriteExternalTemperatureSensor(u8, u8, u8),
     /// Activate display update sequence. BUSY will be high when in progress.
     UpdateDisplay,
@@ -103,36 +251,53 @@ pub enum Command {
     /// 1: Red RAM option
     UpdateDisplayOption1(RamOption, RamOption),
     /// Set display update sequence options
-    UpdateDisplayOption2(u8),
-    // Read from RAM (not implemented)
-    // ReadData,
+    UpdateDisplayOption2(UpdateSequence),
+    /// Read from RAM. The RAM read is selected with `ReadRamOption`.
+    /// The first byte read after this command is a dummy byte.
+    ReadData,
     /// Enter VCOM sensing and hold for duration defined by VCOMSenseDuration
     /// BUSY will be high when in progress.
     EnterVCOMSensing,
     /// Set VCOM sensing duration
+    /// The duration is `value + 1` seconds, up to 16 seconds.
     VCOMSenseDuration(u8),
-    // /// Program VCOM register into OTP
-    // ProgramVCOMIntoOTP,
+    /// Program VCOM register into OTP
+    /// BUSY will be high when in progress.
+    ProgramVCOMIntoOTP,
     /// Write VCOM register from MCU interface
     WriteVCOM(u8),
-    // ReadDisplayOption,
-    // ReadUserId,
-    // StatusBitRead,
-    // ProgramWaveformSetting,
-    // LoadWaveformSetting,
-    // CalculateCRC,
-    // ReadCRC,
-    // ProgramOTP,
-    // WriteDisplayOption,
-    // WriteUserId,
-    // OTPProgramMode,
+    /// Read the display option (11 bytes)
+    ReadDisplayOption,
+    /// Read the user ID stored in OTP (10 bytes)
+    ReadUserId,
+    /// Read the status bits: HV ready, VCI detection and chip ID
+    StatusBitRead,
+    /// Program the waveform setting (LUT) into OTP
+    /// BUSY will be high when in progress.
+    ProgramWaveformSetting,
+    /// Load the waveform setting (LUT) from OTP
+    /// BUSY will be high when in progress.
+    LoadWaveformSetting,
+    /// Calculate the CRC of the OTP. Read result with `ReadCRC` command
+    /// BUSY will be high when in progress.
+    CalculateCRC,
+    /// Read the result of the CRC calculation (2 bytes)
+    ReadCRC,
+    /// Program the OTP selection (display option and user ID) into OTP
+    /// BUSY will be high when in progress.
+    ProgramOTP,
+    /// Set the OTP program mode
+    /// 0b00: Normal mode
+    /// 0b11: Internally generated OTP programming voltage
+    OTPProgramMode(u8),
     /// Set the number of dummy line period in terms of gate line width (TGate)
     DummyLinePeriod(u8),
     /// Set the gate line width (TGate)
     GateLineWidth(u8),
     /// Select border waveform for VBD
-    BorderWaveform(u8),
-    // ReadRamOption,
+    BorderWaveform(BorderWaveform),
+    /// Select the RAM read by `ReadData`
+    ReadRamOption(Ram),
     /// Set the start/end positions of the window address in the X direction
     /// 0: Start
     /// 1: End
@@ -142,24 +307,27 @@ pub enum Command {
     /// 1: End
     StartEndYPosition(u16, u16),
     /// Auto write red RAM for regular pattern
+    /// BUSY will be high when in progress.
     AutoWriteRedPattern(u8),
-    /// Auto write red RAM for regular pattern
+    /// Auto write black/white RAM for regular pattern
+    /// BUSY will be high when in progress.
     AutoWriteBlackPattern(u8),
     /// Set RAM X address
     XAddress(u8),
     /// Set RAM Y address
-    YAddress(u8),
+    YAddress(u16),
     /// Set analog block control
     AnalogBlockControl(u8),
     /// Set digital block control
     DigitalBlockControl(u8),
-    // Used to terminate frame memory reads
-    // Nop,
+    /// Does nothing, used to terminate frame memory reads
+    Nop,
 }
 
 /// Enumerates commands that can be sent to the controller that accept a slice argument buffer. This
 /// is separated from `Command` so that the lifetime parameter of the argument buffer slice does
 /// not pervade code which never invokes these two commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BufCommand<'buf> {
     /// Write to black/white RAM
     /// 1 = White
@@ -171,6 +339,102 @@ pub enum BufCommand<'buf> {
     WriteRedData(&'buf [u8]),
     /// Write LUT register (70 bytes)
     WriteLUT(&'buf [u8]),
+    /// Write the display option register, programmed into OTP by `ProgramOTP` (10 bytes)
+    WriteDisplayOption(&'buf [u8]),
+    /// Write the user ID register, programmed into OTP by `ProgramOTP` (10 bytes)
+    WriteUserId(&'buf [u8]),
+}
+
+/// A command decoded from the bytes sent to the controller.
+///
+/// This is the inverse of executing a `Command` or `BufCommand`, for example to turn a capture of
+/// the SPI bus into a readable transcript. The `Display` output is the command as it would be
+/// written in code, e.g. `DataEntryMode(IncrementYIncrementX, Horizontal)`.
+///
+/// ### Example
+///
+/// ```
+/// use ssd1675::command::{Command, DataEntryMode, Decoded, IncrementAxis};
+///
+/// let decoded = Decoded::decode(0x11, &[0x03]);
+/// assert_eq!(
+///     decoded,
+///     Decoded::Command(Command::DataEntryMode(
+///         DataEntryMode::IncrementYIncrementX,
+///         IncrementAxis::Horizontal
+///     ))
+/// );
+/// assert_eq!(
+///     format!("{}", decoded),
+///     "DataEntryMode(IncrementYIncrementX, Horizontal)"
+/// );
+/// assert_eq!(
+///     format!("{}", Decoded::decode(0x99, &[0x01])),
+///     "Unknown(0x99, [01])"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decoded<'buf> {
+    /// A command with fixed size data
+    Command(Command),
+    /// A command that takes a buffer
+    BufCommand(BufCommand<'buf>),
+    /// The command byte isn't known, or the data is not valid for it
+    /// 0: Command byte
+    /// 1: Data
+    Unknown(u8, &'buf [u8]),
+}
+
+impl<'buf> Decoded<'buf> {
+    /// Decode a command byte and the data sent with it.
+    ///
+    /// The data must be exactly what is sent for the command, otherwise `Decoded::Unknown` is
+    /// returned.
+    pub fn decode(command: u8, data: &'buf [u8]) -> Self {
+        match command {
+            0x24 => Decoded::BufCommand(BufCommand::WriteBlackData(data)),
+            0x26 => Decoded::BufCommand(BufCommand::WriteRedData(data)),
+            0x32 => Decoded::BufCommand(BufCommand::WriteLUT(data)),
+            0x37 => Decoded::BufCommand(BufCommand::WriteDisplayOption(data)),
+            0x38 => Decoded::BufCommand(BufCommand::WriteUserId(data)),
+            _ => match Command::decode(command, data) {
+                Some(command) => Decoded::Command(command),
+                None => Decoded::Unknown(command, data),
+            },
+        }
+    }
+}
+
+impl core::fmt::Display for Decoded<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Decoded::Command(command) => write!(f, "{}", command),
+            Decoded::BufCommand(command) => write!(f, "{}", command),
+            Decoded::Unknown(command, data) => {
+                write!(f, "Unknown(0x{:02X}, {:02X?})", command, data)
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for Command {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl core::fmt::Display for BufCommand<'_> {
+    /// Shows the length of the buffer rather than its contents, e.g. `WriteLUT(70 bytes)`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (name, buffer) = match self {
+            BufCommand::WriteBlackData(buffer) => ("WriteBlackData", buffer),
+            BufCommand::WriteRedData(buffer) => ("WriteRedData", buffer),
+            BufCommand::WriteLUT(buffer) => ("WriteLUT", buffer),
+            BufCommand::WriteDisplayOption(buffer) => ("WriteDisplayOption", buffer),
+            BufCommand::WriteUserId(buffer) => ("WriteUserId", buffer),
+        };
+        write!(f, "{}({} bytes)", name, buffer.len())
+    }
 }
 
 /// Populates data buffer (array) and returns a pair (tuple) with command and
@@ -209,11 +473,43 @@ macro_rules! pack {
 
 impl Command {
     /// Execute the command, transmitting any associated data as well.
-    pub fn execute<I: DisplayInterface>(&self, interface: &mut I) -> Result<(), I::Error> {
-        use self::Command::*;
+    pub fn execute<I: DisplayInterface>(
+        &self,
+        interface: &mut I,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let mut buf = [0u8; 4];
+        let (command, data) = self.encode(&mut buf);
 
+        interface.send_command(command)?;
+        if data.is_empty() {
+            Ok(())
+        } else {
+            interface.send_data(data)
+        }
+    }
+
+    /// Execute the command on an async interface, transmitting any associated data as well.
+    #[cfg(feature = "async")]
+    pub async fn execute_async<I: AsyncDisplayInterface>(
+        &self,
+        interface: &mut I,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
         let mut buf = [0u8; 4];
-        let (command, data) = match *self {
+        let (command, data) = self.encode(&mut buf);
+
+        interface.send_command(command).await?;
+        if data.is_empty() {
+            Ok(())
+        } else {
+            interface.send_data(data).await
+        }
+    }
+
+    /// Encode the command into its command byte and data, using `buf` to hold the data.
+    fn encode<'b>(&self, buf: &'b mut [u8; 4]) -> (u8, &'b [u8]) {
+        use self::Command::*;
+
+        match *self {
             DriverOutputControl(gate_lines, scanning_seq_and_dir) => {
                 let [upper, lower] = gate_lines.to_be_bytes();
                 pack!(buf, 0x01, [lower, upper, scanning_seq_and_dir])
@@ -252,71 +548,283 @@ impl Command {
                 pack!(buf, 0x11, [axis | mode])
             }
             SoftReset => pack!(buf, 0x12, []),
-            // TemperatatSensorSelection(TemperatureSensor) => {
-            // }
-            // WriteTemperatureSensor(u16) => {
-            // }
-            // ReadTemperatureSensor(u16) => {
-            // }
-            // WriteExternalTemperatureSensor(u8, u8, u8) => {
-            // }
+            StartHVReadyDetection => pack!(buf, 0x14, []),
+            StartVCILevelDetection(threshold) => {
+                debug_assert!(Contains::contains(
+                    &(0..=MAX_VCI_LEVEL_THRESHOLD),
+                    threshold
+                ));
+                pack!(buf, 0x15, [threshold])
+            }
+            TemperatatSensorSelection(sensor) => {
+                let sensor = match sensor {
+                    TemperatureSensor::Internal => 0x80,
+                    TemperatureSensor::External => 0x48,
+                };
+
+                pack!(buf, 0x18, [sensor])
+            }
+            WriteTemperatureSensor(value) => {
+                debug_assert!(Contains::contains(&(0..=MAX_TEMPERATURE_REGISTER), value));
+                let [upper, lower] = (value << 4).to_be_bytes();
+                pack!(buf, 0x1A, [upper, lower])
+            }
+            ReadTemperatureSensor => pack!(buf, 0x1B, []),
+            WriteExternalTemperatureSensor(config, command1, command2) => {
+                pack!(buf, 0x1C, [config, command1, command2])
+            }
             UpdateDisplay => pack!(buf, 0x20, []),
-            // UpdateDisplayOption1(RamOption, RamOption) => {
-            // }
-            UpdateDisplayOption2(value) => pack!(buf, 0x22, [value]),
-            // EnterVCOMSensing => {
-            // }
-            // VCOMSenseDuration(u8) => {
-            // }
+            UpdateDisplayOption1(black_white, red) => {
+                let option = |option| match option {
+                    RamOption::Normal => 0b0000,
+                    RamOption::Bypass => 0b0100,
+                    RamOption::Invert => 0b1000,
+                };
+
+                pack!(buf, 0x21, [option(red) << 4 | option(black_white)])
+            }
+            UpdateDisplayOption2(sequence) => pack!(buf, 0x22, [sequence.bits()]),
+            ReadData => pack!(buf, 0x27, []),
+            EnterVCOMSensing => pack!(buf, 0x28, []),
+            VCOMSenseDuration(duration) => {
+                debug_assert!(Contains::contains(&(0..=MAX_VCOM_SENSE_DURATION), duration));
+                pack!(buf, 0x29, [duration])
+            }
+            ProgramVCOMIntoOTP => pack!(buf, 0x2A, []),
             WriteVCOM(value) => pack!(buf, 0x2C, [value]),
+            ReadDisplayOption => pack!(buf, 0x2D, []),
+            ReadUserId => pack!(buf, 0x2E, []),
+            StatusBitRead => pack!(buf, 0x2F, []),
+            ProgramWaveformSetting => pack!(buf, 0x30, []),
+            LoadWaveformSetting => pack!(buf, 0x31, []),
+            CalculateCRC => pack!(buf, 0x34, []),
+            ReadCRC => pack!(buf, 0x35, []),
+            ProgramOTP => pack!(buf, 0x36, []),
+            OTPProgramMode(mode) => pack!(buf, 0x39, [mode & 0b11]),
             DummyLinePeriod(period) => {
                 debug_assert!(Contains::contains(&(0..=MAX_DUMMY_LINE_PERIOD), period));
                 pack!(buf, 0x3A, [period])
             }
             GateLineWidth(tgate) => pack!(buf, 0x3B, [tgate]),
-            BorderWaveform(border_waveform) => pack!(buf, 0x3C, [border_waveform]),
+            BorderWaveform(border_waveform) => {
+                let lut = |lut| match lut {
+                    BorderLut::Lut0 => 0b00,
+                    BorderLut::Lut1 => 0b01,
+                    BorderLut::Lut2 => 0b10,
+                    BorderLut::Lut3 => 0b11,
+                };
+                let border_waveform = match border_waveform {
+                    self::BorderWaveform::HiZ => 0b1100_0000,
+                    self::BorderWaveform::FixLevel(level) => 0b0100_0000 | (level as u8) << 4,
+                    self::BorderWaveform::Vcom => 0b1000_0000,
+                    self::BorderWaveform::FollowLut(border_lut) => 0b0000_0100 | lut(border_lut),
+                    self::BorderWaveform::GsTransition(border_lut) => lut(border_lut),
+                };
+
+                pack!(buf, 0x3C, [border_waveform])
+            }
+            ReadRamOption(ram) => {
+                let ram = match ram {
+                    Ram::BlackWhite => 0b0,
+                    Ram::Red => 0b1,
+                };
+
+                pack!(buf, 0x41, [ram])
+            }
             StartEndXPosition(start, end) => pack!(buf, 0x44, [start, end]),
             StartEndYPosition(start, end) => {
                 let [start_upper, start_lower] = start.to_be_bytes();
                 let [end_upper, end_lower] = end.to_be_bytes();
                 pack!(buf, 0x45, [start_lower, start_upper, end_lower, end_upper])
             }
-            // AutoWriteRedPattern(u8) => {
-            // }
-            // AutoWriteBlackPattern(u8) => {
-            // }
+            AutoWriteRedPattern(pattern) => pack!(buf, 0x46, [pattern]),
+            AutoWriteBlackPattern(pattern) => pack!(buf, 0x47, [pattern]),
             XAddress(address) => pack!(buf, 0x4E, [address]),
-            YAddress(address) => pack!(buf, 0x4F, [address]),
+            YAddress(address) => {
+                let [upper, lower] = address.to_be_bytes();
+                pack!(buf, 0x4F, [lower, upper])
+            }
             AnalogBlockControl(value) => pack!(buf, 0x74, [value]),
             DigitalBlockControl(value) => pack!(buf, 0x7E, [value]),
-            _ => unimplemented!(),
+            Nop => pack!(buf, 0x7F, []),
+        }
+    }
+
+    /// Decode a command byte and its data, the inverse of `encode`.
+    fn decode(command: u8, data: &[u8]) -> Option<Self> {
+        use self::Command::*;
+
+        let ram_option = |option| match option {
+            0b0000 => Some(RamOption::Normal),
+            0b0100 => Some(RamOption::Bypass),
+            0b1000 => Some(RamOption::Invert),
+            _ => None,
+        };
+        let border_lut = |lut| match lut & 0b11 {
+            0b00 => BorderLut::Lut0,
+            0b01 => BorderLut::Lut1,
+            0b10 => BorderLut::Lut2,
+            _ => BorderLut::Lut3,
+        };
+
+        let command = match (command, data) {
+            (0x01, &[lower, upper, scanning_seq_and_dir]) => {
+                DriverOutputControl(u16::from_le_bytes([lower, upper]), scanning_seq_and_dir)
+            }
+            (0x03, &[voltages]) => GateDrivingVoltage(voltages),
+            (0x04, &[vsh1, vsh2, vsl]) => SourceDrivingVoltage(vsh1, vsh2, vsl),
+            (0x0C, &[phase1, phase2, phase3, duration]) => {
+                BoosterEnable(phase1, phase2, phase3, duration)
+            }
+            (0x0F, &[lower, upper]) => GateScanStartPostion(u16::from_le_bytes([lower, upper])),
+            (0x10, &[mode]) => DeepSleepMode(match mode {
+                0b00 => self::DeepSleepMode::Normal,
+                0b01 => self::DeepSleepMode::PreserveRAM,
+                0b11 => self::DeepSleepMode::DiscardRAM,
+                _ => return None,
+            }),
+            (0x11, &[mode]) if mode & !0b111 == 0 => {
+                let data_entry_mode = match mode & 0b11 {
+                    0b00 => self::DataEntryMode::DecrementXDecrementY,
+                    0b01 => self::DataEntryMode::IncrementXDecrementY,
+                    0b10 => self::DataEntryMode::DecrementXIncrementY,
+                    _ => self::DataEntryMode::IncrementYIncrementX,
+                };
+                let increment_axis = if mode & 0b100 == 0 {
+                    IncrementAxis::Horizontal
+                } else {
+                    IncrementAxis::Vertical
+                };
+                DataEntryMode(data_entry_mode, increment_axis)
+            }
+            (0x12, &[]) => SoftReset,
+            (0x14, &[]) => StartHVReadyDetection,
+            (0x15, &[threshold]) if threshold <= MAX_VCI_LEVEL_THRESHOLD => {
+                StartVCILevelDetection(threshold)
+            }
+            (0x18, &[0x80]) => TemperatatSensorSelection(TemperatureSensor::Internal),
+            (0x18, &[0x48]) => TemperatatSensorSelection(TemperatureSensor::External),
+            (0x1A, &[upper, lower]) if lower & 0x0F == 0 => {
+                WriteTemperatureSensor(u16::from_be_bytes([upper, lower]) >> 4)
+            }
+            (0x1B, &[]) => ReadTemperatureSensor,
+            (0x1C, &[config, command1, command2]) => {
+                WriteExternalTemperatureSensor(config, command1, command2)
+            }
+            (0x20, &[]) => UpdateDisplay,
+            (0x21, &[options]) => {
+                UpdateDisplayOption1(ram_option(options & 0x0F)?, ram_option(options >> 4)?)
+            }
+            (0x22, &[value]) => UpdateDisplayOption2(UpdateSequence::from_bits(value)),
+            (0x27, &[]) => ReadData,
+            (0x28, &[]) => EnterVCOMSensing,
+            (0x29, &[duration]) if duration <= MAX_VCOM_SENSE_DURATION => {
+                VCOMSenseDuration(duration)
+            }
+            (0x2A, &[]) => ProgramVCOMIntoOTP,
+            (0x2C, &[value]) => WriteVCOM(value),
+            (0x2D, &[]) => ReadDisplayOption,
+            (0x2E, &[]) => ReadUserId,
+            (0x2F, &[]) => StatusBitRead,
+            (0x30, &[]) => ProgramWaveformSetting,
+            (0x31, &[]) => LoadWaveformSetting,
+            (0x34, &[]) => CalculateCRC,
+            (0x35, &[]) => ReadCRC,
+            (0x36, &[]) => ProgramOTP,
+            (0x39, &[mode]) if mode <= 0b11 => OTPProgramMode(mode),
+            (0x3A, &[period]) if period <= MAX_DUMMY_LINE_PERIOD => DummyLinePeriod(period),
+            (0x3B, &[tgate]) => GateLineWidth(tgate),
+            (0x3C, &[0b1100_0000]) => BorderWaveform(self::BorderWaveform::HiZ),
+            (0x3C, &[0b1000_0000]) => BorderWaveform(self::BorderWaveform::Vcom),
+            (0x3C, &[border_waveform]) if border_waveform & 0b1100_1111 == 0b0100_0000 => {
+                BorderWaveform(self::BorderWaveform::FixLevel(Voltage::from_bits(
+                    border_waveform >> 4,
+                )))
+            }
+            (0x3C, &[border_waveform]) if border_waveform & 0b1111_1000 == 0 => {
+                let lut = border_lut(border_waveform);
+                BorderWaveform(if border_waveform & 0b100 != 0 {
+                    self::BorderWaveform::FollowLut(lut)
+                } else {
+                    self::BorderWaveform::GsTransition(lut)
+                })
+            }
+            (0x41, &[0b0]) => ReadRamOption(Ram::BlackWhite),
+            (0x41, &[0b1]) => ReadRamOption(Ram::Red),
+            (0x44, &[start, end]) => StartEndXPosition(start, end),
+            (0x45, &[start_lower, start_upper, end_lower, end_upper]) => StartEndYPosition(
+                u16::from_le_bytes([start_lower, start_upper]),
+                u16::from_le_bytes([end_lower, end_upper]),
+            ),
+            (0x46, &[pattern]) => AutoWriteRedPattern(pattern),
+            (0x47, &[pattern]) => AutoWriteBlackPattern(pattern),
+            (0x4E, &[address]) => XAddress(address),
+            (0x4F, &[lower, upper]) => YAddress(u16::from_le_bytes([lower, upper])),
+            (0x74, &[value]) => AnalogBlockControl(value),
+            (0x7E, &[value]) => DigitalBlockControl(value),
+            (0x7F, &[]) => Nop,
+            _ => return None,
         };
 
+        Some(command)
+    }
+
+    /// Execute the command then read the response from the controller into `buffer`.
+    ///
+    /// Intended for the read commands: `ReadTemperatureSensor`, `ReadData`, `ReadDisplayOption`,
+    /// `ReadUserId`, `StatusBitRead`, and `ReadCRC`.
+    pub fn read<I: ReadInterface>(
+        &self,
+        interface: &mut I,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.execute(interface)?;
+        interface.read_data(buffer)
+    }
+}
+
+impl<'buf> BufCommand<'buf> {
+    /// Execute the command, transmitting the associated buffer as well.
+    pub fn execute<I: DisplayInterface>(
+        &self,
+        interface: &mut I,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let (command, data) = self.encode();
+
         interface.send_command(command)?;
-        if data.len() == 0 {
+        if data.is_empty() {
             Ok(())
         } else {
             interface.send_data(data)
         }
     }
-}
 
-impl<'buf> BufCommand<'buf> {
-    /// Execute the command, transmitting the associated buffer as well.
-    pub fn execute<I: DisplayInterface>(&self, interface: &mut I) -> Result<(), I::Error> {
+    /// Execute the command on an async interface, transmitting the associated buffer as well.
+    #[cfg(feature = "async")]
+    pub async fn execute_async<I: AsyncDisplayInterface>(
+        &self,
+        interface: &mut I,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let (command, data) = self.encode();
+
+        interface.send_command(command).await?;
+        if data.is_empty() {
+            Ok(())
+        } else {
+            interface.send_data(data).await
+        }
+    }
+
+    /// Returns the command byte and the buffer to send with it.
+    fn encode(&self) -> (u8, &'buf [u8]) {
         use self::BufCommand::*;
 
-        let (command, data) = match self {
+        match *self {
             WriteBlackData(buffer) => (0x24, buffer),
             WriteRedData(buffer) => (0x26, buffer),
             WriteLUT(buffer) => (0x32, buffer),
-        };
-
-        interface.send_command(command)?;
-        if data.len() == 0 {
-            Ok(())
-        } else {
-            interface.send_data(data)
+            WriteDisplayOption(buffer) => (0x37, buffer),
+            WriteUserId(buffer) => (0x38, buffer),
         }
     }
 }
@@ -367,19 +875,23 @@ mod tests {
     }
 
     impl DisplayInterface for MockInterface {
-        type Error = ();
+        type SpiError = ();
+        type PinError = ();
 
         /// Send a command to the controller.
         ///
         /// Prefer calling `execute` on a [Commmand](../command/enum.Command.html) over calling this
         /// directly.
-        fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        fn send_command(
+            &mut self,
+            command: u8,
+        ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
             self.write(command);
             Ok(())
         }
 
         /// Send data for a command.
-        fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
             for byte in data {
                 self.write(*byte)
             }
@@ -387,14 +899,27 @@ mod tests {
         }
 
         /// Reset the controller.
-        fn reset<D: hal::blocking::delay::DelayMs<u8>>(&mut self, _delay: &mut D) {
+        fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+        ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
             self.data = [0; 256];
             self.offset = 0;
+            Ok(())
         }
 
         /// Wait for the controller to indicate it is not busy.
-        fn busy_wait(&self) {
+        fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+            _timeout_ms: u32,
+        ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
             // nop
+            Ok(())
+        }
+
+        fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+            Ok(false)
         }
     }
 
@@ -412,4 +937,216 @@ mod tests {
             &[0x01, lower, upper, scanning_seq_and_dir]
         );
     }
+
+    #[test]
+    fn test_temperature_commands() {
+        let mut interface = MockInterface::new();
+        Command::TemperatatSensorSelection(TemperatureSensor::Internal)
+            .execute(&mut interface)
+            .unwrap();
+        Command::WriteTemperatureSensor(0x7F1)
+            .execute(&mut interface)
+            .unwrap();
+        Command::ReadTemperatureSensor
+            .execute(&mut interface)
+            .unwrap();
+        Command::WriteExternalTemperatureSensor(0x40, 0x12, 0x34)
+            .execute(&mut interface)
+            .unwrap();
+
+        assert_eq!(
+            interface.data(),
+            &[0x18, 0x80, 0x1A, 0x7F, 0x10, 0x1B, 0x1C, 0x40, 0x12, 0x34]
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let commands = [
+            (
+                Command::BoosterEnable(0x8B, 0x9C, 0x96, 0x0F),
+                &[0x0C, 0x8B, 0x9C, 0x96, 0x0F][..],
+            ),
+            (Command::StartHVReadyDetection, &[0x14]),
+            (Command::StartVCILevelDetection(0x03), &[0x15, 0x03]),
+            (
+                Command::UpdateDisplayOption1(RamOption::Invert, RamOption::Bypass),
+                &[0x21, 0x48],
+            ),
+            (Command::EnterVCOMSensing, &[0x28]),
+            (Command::VCOMSenseDuration(0x09), &[0x29, 0x09]),
+            (Command::ProgramVCOMIntoOTP, &[0x2A]),
+            (Command::ProgramWaveformSetting, &[0x30]),
+            (Command::LoadWaveformSetting, &[0x31]),
+            (Command::CalculateCRC, &[0x34]),
+            (Command::ProgramOTP, &[0x36]),
+            (Command::OTPProgramMode(0b11), &[0x39, 0x03]),
+            (Command::AutoWriteRedPattern(0x15), &[0x46, 0x15]),
+            (Command::AutoWriteBlackPattern(0x95), &[0x47, 0x95]),
+            (Command::Nop, &[0x7F]),
+        ];
+        for (command, expected) in commands.iter() {
+            let mut interface = MockInterface::new();
+            command.execute(&mut interface).unwrap();
+            assert_eq!(interface.data(), *expected);
+        }
+
+        let mut interface = MockInterface::new();
+        BufCommand::WriteDisplayOption(&[1, 2])
+            .execute(&mut interface)
+            .unwrap();
+        BufCommand::WriteUserId(&[3, 4])
+            .execute(&mut interface)
+            .unwrap();
+        assert_eq!(interface.data(), &[0x37, 1, 2, 0x38, 3, 4]);
+    }
+
+    #[test]
+    fn test_decode() {
+        let commands = [
+            Command::DriverOutputControl(0x127, 0x01),
+            Command::GateDrivingVoltage(0x11),
+            Command::SourceDrivingVoltage(0x2D, 0xB2, 0x22),
+            Command::BoosterEnable(0x8B, 0x9C, 0x96, 0x0F),
+            Command::GateScanStartPostion(0x105),
+            Command::DeepSleepMode(DeepSleepMode::Normal),
+            Command::DeepSleepMode(DeepSleepMode::PreserveRAM),
+            Command::DeepSleepMode(DeepSleepMode::DiscardRAM),
+            Command::DataEntryMode(DataEntryMode::IncrementXDecrementY, IncrementAxis::Vertical),
+            Command::SoftReset,
+            Command::StartHVReadyDetection,
+            Command::StartVCILevelDetection(0x05),
+            Command::TemperatatSensorSelection(TemperatureSensor::Internal),
+            Command::TemperatatSensorSelection(TemperatureSensor::External),
+            Command::WriteTemperatureSensor(0x7F1),
+            Command::ReadTemperatureSensor,
+            Command::WriteExternalTemperatureSensor(0x40, 0x12, 0x34),
+            Command::UpdateDisplay,
+            Command::UpdateDisplayOption1(RamOption::Normal, RamOption::Invert),
+            Command::UpdateDisplayOption2(UpdateSequence::FULL),
+            Command::ReadData,
+            Command::EnterVCOMSensing,
+            Command::VCOMSenseDuration(0x0F),
+            Command::ProgramVCOMIntoOTP,
+            Command::WriteVCOM(0x3C),
+            Command::ReadDisplayOption,
+            Command::ReadUserId,
+            Command::StatusBitRead,
+            Command::ProgramWaveformSetting,
+            Command::LoadWaveformSetting,
+            Command::CalculateCRC,
+            Command::ReadCRC,
+            Command::ProgramOTP,
+            Command::OTPProgramMode(0b11),
+            Command::DummyLinePeriod(0x07),
+            Command::GateLineWidth(0x04),
+            Command::BorderWaveform(BorderWaveform::HiZ),
+            Command::BorderWaveform(BorderWaveform::FixLevel(Voltage::Vsh2)),
+            Command::BorderWaveform(BorderWaveform::Vcom),
+            Command::BorderWaveform(BorderWaveform::FollowLut(BorderLut::Lut2)),
+            Command::BorderWaveform(BorderWaveform::GsTransition(BorderLut::Lut0)),
+            Command::ReadRamOption(Ram::BlackWhite),
+            Command::ReadRamOption(Ram::Red),
+            Command::StartEndXPosition(1, 12),
+            Command::StartEndYPosition(0x127, 0x10),
+            Command::AutoWriteRedPattern(0x15),
+            Command::AutoWriteBlackPattern(0x95),
+            Command::XAddress(3),
+            Command::YAddress(0x101),
+            Command::AnalogBlockControl(0x54),
+            Command::DigitalBlockControl(0x3B),
+            Command::Nop,
+        ];
+        for command in commands.iter() {
+            let mut buf = [0u8; 4];
+            let (command_byte, data) = command.encode(&mut buf);
+            assert_eq!(
+                Decoded::decode(command_byte, data),
+                Decoded::Command(*command)
+            );
+        }
+
+        let lut = [0u8; 70];
+        assert_eq!(
+            Decoded::decode(0x32, &lut),
+            Decoded::BufCommand(BufCommand::WriteLUT(&lut))
+        );
+        assert_eq!(
+            Decoded::decode(0x10, &[0b10]),
+            Decoded::Unknown(0x10, &[0b10])
+        );
+        assert_eq!(Decoded::decode(0x22, &[]), Decoded::Unknown(0x22, &[]));
+        assert_eq!(Decoded::decode(0x12, &[0]), Decoded::Unknown(0x12, &[0]));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            format!("{}", Decoded::decode(0x01, &[0x27, 0x01, 0x00])),
+            "DriverOutputControl(295, 0)"
+        );
+        assert_eq!(
+            format!("{}", Decoded::decode(0x3C, &[0x60])),
+            "BorderWaveform(FixLevel(Vsl))"
+        );
+        assert_eq!(
+            format!("{}", Decoded::decode(0x24, &[0xFF; 12])),
+            "WriteBlackData(12 bytes)"
+        );
+        assert_eq!(
+            format!("{}", Decoded::decode(0x22, &[0xC7, 0x01])),
+            "Unknown(0x22, [C7, 01])"
+        );
+    }
+
+    #[test]
+    fn test_update_sequence() {
+        let power_up = UpdateSequence::new().enable_clock().enable_analog();
+        let power_down = UpdateSequence::new().disable_analog().disable_clock();
+        assert_eq!(power_up.bits(), 0xC0);
+        assert_eq!(power_down.bits(), 0x03);
+        assert_eq!(
+            power_up
+                .display(DisplayMode::Mode2)
+                .disable_analog()
+                .disable_clock(),
+            UpdateSequence::FAST
+        );
+        assert_eq!(
+            power_up
+                .load_temperature()
+                .load_lut()
+                .display(DisplayMode::Mode1)
+                .bits(),
+            0xF4
+        );
+        // The last display mode chosen wins
+        assert_eq!(
+            UpdateSequence::FAST.display(DisplayMode::Mode1),
+            UpdateSequence::FULL
+        );
+        assert_eq!(UpdateSequence::from_bits(0xA1).bits(), 0xA1);
+    }
+
+    #[test]
+    fn test_border_waveform() {
+        let mut interface = MockInterface::new();
+        let border_waveforms = [
+            BorderWaveform::HiZ,
+            BorderWaveform::FixLevel(Voltage::Vsl),
+            BorderWaveform::Vcom,
+            BorderWaveform::FollowLut(BorderLut::Lut1),
+            BorderWaveform::GsTransition(BorderLut::Lut3),
+        ];
+        for border_waveform in border_waveforms {
+            Command::BorderWaveform(border_waveform)
+                .execute(&mut interface)
+                .unwrap();
+        }
+
+        assert_eq!(
+            interface.data(),
+            &[0x3C, 0xC0, 0x3C, 0x60, 0x3C, 0x80, 0x3C, 0x05, 0x3C, 0x03]
+        );
+    }
 }
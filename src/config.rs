@@ -1,5 +1,17 @@
-use command::{BufCommand, Command, DataEntryMode, IncrementAxis};
-use display::{self, Dimensions, Rotation};
+use core::ops::Range;
+use core::time::Duration;
+
+use crate::command::{
+    BorderWaveform, BufCommand, Command, DataEntryMode, IncrementAxis, RamOption,
+    TemperatureSensor, UpdateSequence,
+};
+use crate::display::{self, Dimensions, Rotation};
+use crate::waveform::Waveform;
+
+/// TGate in microseconds for each value of the gate line width register (command 0x3B).
+const GATE_LINE_WIDTHS_US: [u32; 16] = [
+    30, 34, 38, 40, 44, 46, 52, 56, 62, 68, 78, 88, 104, 125, 156, 208,
+];
 
 /// Builder for constructing a display Config.
 ///
@@ -21,15 +33,246 @@ use display::{self, Dimensions, Rotation};
 ///     .expect("invalid configuration");
 /// ```
 pub struct Builder<'a> {
-    dummy_line_period: Command,
-    gate_line_width: Command,
+    dummy_line_period: u8,
+    gate_line_width: u8,
     write_vcom: Command,
     write_lut: Option<BufCommand<'a>>,
+    temperature_luts: &'a [TemperatureLut<'a>],
+    write_fast_lut: Option<BufCommand<'a>>,
     data_entry_mode: Command,
+    temperature_sensor: Option<Command>,
+    border_waveform: Option<Command>,
+    ram_options: Option<Command>,
+    update_sequence: UpdateSequence,
+    gate_voltage: Option<Command>,
+    source_voltages: Option<Command>,
+    booster_soft_start: Option<Command>,
+    busy_timeout_ms: u32,
     dimensions: Option<Dimensions>,
     rotation: Rotation,
 }
 
+/// A lookup table that applies to a range of temperatures.
+///
+/// ### Example
+///
+/// ```
+/// use ssd1675::config::TemperatureLut;
+///
+/// # const COLD_LUT: [u8; 70] = [0; 70];
+/// # const WARM_LUT: [u8; 70] = [0; 70];
+/// const LUTS: [TemperatureLut; 2] = [
+///     TemperatureLut { temperatures: -40..10, lut: &COLD_LUT },
+///     TemperatureLut { temperatures: 10..50, lut: &WARM_LUT },
+/// ];
+/// ```
+pub struct TemperatureLut<'a> {
+    /// The range of temperatures in degrees Celsius this LUT is used for. The start is
+    /// inclusive, the end exclusive.
+    pub temperatures: Range<i8>,
+    /// The lookup table (70 bytes).
+    pub lut: &'a [u8],
+}
+
+impl<'a> TemperatureLut<'a> {
+    pub(crate) fn covers(&self, celsius: f32) -> bool {
+        celsius >= f32::from(self.temperatures.start) && celsius < f32::from(self.temperatures.end)
+    }
+}
+
+/// The number of `step`s from `min` to `value`, if `value` rounded to the nearest step is within
+/// `min..=max`.
+fn steps(value: f32, min: f32, max: f32, step: f32) -> Option<u8> {
+    let steps = libm::roundf((value - min) / step);
+    if steps >= 0. && steps <= libm::roundf((max - min) / step) {
+        Some(steps as u8)
+    } else {
+        None
+    }
+}
+
+/// The gate driving voltage (VGH).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GateVoltage(u8);
+
+impl GateVoltage {
+    /// VGH in volts, from 10V to 20V in steps of 0.5V.
+    ///
+    /// The voltage is rounded to the nearest step. Returns `None` if it is out of range.
+    pub fn from_volts(volts: f32) -> Option<Self> {
+        steps(volts, 10., 20., 0.5).map(|steps| GateVoltage(0x03 + steps))
+    }
+
+    /// The voltage in volts.
+    pub fn volts(self) -> f32 {
+        10. + 0.5 * f32::from(self.0 - 0x03)
+    }
+
+    /// The value of the gate driving voltage register.
+    pub fn register(self) -> u8 {
+        self.0
+    }
+}
+
+/// A positive source driving voltage (VSH1 or VSH2).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SourceVoltage(u8);
+
+impl SourceVoltage {
+    /// VSH in volts, from 2.4V to 8.8V in steps of 0.1V or 9V to 17V in steps of 0.2V.
+    ///
+    /// The voltage is rounded to the nearest step across both ranges, so between 8.8V and 9V it
+    /// snaps to whichever is closer, with 8.9V rounding up. Returns `None` if it is out of range.
+    pub fn from_volts(volts: f32) -> Option<Self> {
+        if volts < 8.9 {
+            steps(volts.min(8.8), 2.4, 8.8, 0.1).map(|steps| SourceVoltage(0x8E + steps))
+        } else {
+            steps(volts.max(9.), 9., 17., 0.2).map(|steps| SourceVoltage(0x23 + steps))
+        }
+    }
+
+    /// The voltage in volts.
+    pub fn volts(self) -> f32 {
+        if self.0 & 0x80 != 0 {
+            2.4 + 0.1 * f32::from(self.0 - 0x8E)
+        } else {
+            9. + 0.2 * f32::from(self.0 - 0x23)
+        }
+    }
+
+    /// The value of the source driving voltage register.
+    pub fn register(self) -> u8 {
+        self.0
+    }
+}
+
+/// The negative source driving voltage (VSL).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NegativeSourceVoltage(u8);
+
+impl NegativeSourceVoltage {
+    /// VSL in volts, from -5V to -17V in steps of 0.5V.
+    ///
+    /// The voltage is rounded to the nearest step. Returns `None` if it is out of range.
+    pub fn from_volts(volts: f32) -> Option<Self> {
+        steps(-volts, 5., 17., 0.5).map(|steps| NegativeSourceVoltage(0x0A + 2 * steps))
+    }
+
+    /// The voltage in volts.
+    pub fn volts(self) -> f32 {
+        -5. - 0.25 * f32::from(self.0 - 0x0A)
+    }
+
+    /// The value of the source driving voltage register.
+    pub fn register(self) -> u8 {
+        self.0
+    }
+}
+
+/// The VCOM DC level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vcom(u8);
+
+impl Vcom {
+    /// VCOM in volts, from -0.2V to -3V in steps of 0.025V.
+    ///
+    /// The voltage is rounded to the nearest step. Returns `None` if it is out of range.
+    pub fn from_volts(volts: f32) -> Option<Self> {
+        steps(-volts, 0.2, 3., 0.025).map(|steps| Vcom(0x08 + steps))
+    }
+
+    /// A VCOM register value, as sensed by the controller.
+    pub(crate) fn from_register(register: u8) -> Self {
+        Vcom(register)
+    }
+
+    /// The voltage in volts.
+    pub fn volts(self) -> f32 {
+        -f32::from(self.0) / 40.
+    }
+
+    /// The value of the VCOM register.
+    pub fn register(self) -> u8 {
+        self.0
+    }
+}
+
+/// How long the controller senses VCOM for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VcomSenseDuration(u8);
+
+impl VcomSenseDuration {
+    /// A duration in seconds, from 1 to 16.
+    ///
+    /// Returns `None` if it is out of range.
+    pub fn from_secs(seconds: u8) -> Option<Self> {
+        if (1..=16).contains(&seconds) {
+            Some(VcomSenseDuration(seconds - 1))
+        } else {
+            None
+        }
+    }
+
+    /// The duration in seconds.
+    pub fn secs(self) -> u8 {
+        self.0 + 1
+    }
+
+    /// The value of the VCOM sense duration register.
+    pub fn register(self) -> u8 {
+        self.0
+    }
+}
+
+/// The soft start setting for one phase of the booster.
+///
+/// The power on default is strength 1, minimum off time 0xB and 40ms for phase 1, strength 2,
+/// minimum off time 0xC and 40ms for phase 2, and strength 2, minimum off time 0x6 and 10ms for
+/// phase 3.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoosterPhase {
+    strength: u8,
+    min_off_time: u8,
+    duration_ms: u8,
+}
+
+impl BoosterPhase {
+    /// A phase with driving `strength` from 1 (weakest) to 8 (strongest), minimum off time of
+    /// GDR `min_off_time` from 0x4 (2.6µs) to 0xF (16.5µs), lasting `duration_ms` milliseconds:
+    /// 10, 20, 30, or 40.
+    ///
+    /// Returns `None` if any of them is out of range.
+    pub fn new(strength: u8, min_off_time: u8, duration_ms: u8) -> Option<Self> {
+        if (1..=8).contains(&strength)
+            && (0x4..=0xF).contains(&min_off_time)
+            && [10, 20, 30, 40].contains(&duration_ms)
+        {
+            Some(BoosterPhase {
+                strength,
+                min_off_time,
+                duration_ms,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The driving strength.
+    pub fn strength(self) -> u8 {
+        self.strength
+    }
+
+    /// The minimum off time of GDR.
+    pub fn min_off_time(self) -> u8 {
+        self.min_off_time
+    }
+
+    /// The duration of the phase in milliseconds.
+    pub fn duration_ms(self) -> u8 {
+        self.duration_ms
+    }
+}
+
 /// Error returned if Builder configuration is invalid.
 ///
 /// Currently only returned if a configuration is built without dimensions.
@@ -39,27 +282,121 @@ pub struct BuilderError {}
 /// Display configuration.
 ///
 /// Passed to Display::new. Use `Builder` to construct a `Config`.
+///
+/// The timing of an update follows from the configuration. Each frame scans every gate line plus
+/// the dummy lines, taking one gate line width (TGate) for each, and a LUT runs for a fixed number
+/// of frames. This allows the time a full update takes to be estimated, for example to size
+/// `Builder::busy_timeout_ms` or a watchdog.
+///
+/// ```
+/// use core::time::Duration;
+/// use ssd1675::waveform::{Phase, Waveform};
+/// use ssd1675::{Builder, Dimensions};
+///
+/// const LUT: [u8; 70] = Waveform::new()
+///     .phase(0, Phase { durations: [50, 0, 0, 0], repeat: 1, ..Phase::EMPTY })
+///     .to_bytes();
+///
+/// let config = Builder::new()
+///     .dimensions(Dimensions { rows: 212, cols: 104 })
+///     .dummy_line_period(0x07)
+///     .gate_line_width(0x04)
+///     .lut(&LUT)
+///     .build()
+///     .expect("invalid configuration");
+///
+/// // (212 + 7) lines of 44µs each
+/// assert_eq!(config.frame_period(), Duration::from_micros(9_636));
+/// // The phase runs for 50 frames twice
+/// assert_eq!(config.refresh_duration(), Some(Duration::from_micros(963_600)));
+/// // Within the 1s recommended for the panel
+/// assert_eq!(config.refresh_overrun(Duration::from_secs(1)), None);
+/// ```
 pub struct Config<'a> {
-    pub(crate) dummy_line_period: Command,
-    pub(crate) gate_line_width: Command,
+    pub(crate) dummy_line_period: u8,
+    pub(crate) gate_line_width: u8,
     pub(crate) write_vcom: Command,
     pub(crate) write_lut: Option<BufCommand<'a>>,
+    pub(crate) temperature_luts: &'a [TemperatureLut<'a>],
+    pub(crate) write_fast_lut: Option<BufCommand<'a>>,
     pub(crate) data_entry_mode: Command,
+    pub(crate) temperature_sensor: Option<Command>,
+    pub(crate) border_waveform: Option<Command>,
+    pub(crate) ram_options: Option<Command>,
+    pub(crate) update_sequence: UpdateSequence,
+    pub(crate) gate_voltage: Option<Command>,
+    pub(crate) source_voltages: Option<Command>,
+    pub(crate) booster_soft_start: Option<Command>,
+    pub(crate) busy_timeout_ms: u32,
     pub(crate) dimensions: Dimensions,
     pub(crate) rotation: Rotation,
 }
 
+impl<'a> Config<'a> {
+    /// The time taken to scan one frame.
+    ///
+    /// This is the number of rows plus the dummy line period, multiplied by the gate line width.
+    pub fn frame_period(&self) -> Duration {
+        let lines = u32::from(self.dimensions.rows) + u32::from(self.dummy_line_period);
+        let tgate_us = GATE_LINE_WIDTHS_US[usize::from(self.gate_line_width & 0x0F)];
+
+        Duration::from_micros(u64::from(lines * tgate_us))
+    }
+
+    /// The frame frequency in Hz.
+    pub fn frame_frequency(&self) -> f32 {
+        1. / self.frame_period().as_secs_f32()
+    }
+
+    /// Estimate how long a full update takes with the LUT set by `Builder::lut`.
+    ///
+    /// Returns `None` if no LUT was supplied, since the timing of the LUT built into the
+    /// controller isn't known, or if the LUT is not 70 bytes. The estimate for any other LUT can
+    /// be found with `Waveform::refresh_duration` and `frame_period`.
+    pub fn refresh_duration(&self) -> Option<Duration> {
+        match self.write_lut {
+            Some(BufCommand::WriteLUT(lut)) => Waveform::from_bytes(lut)
+                .ok()
+                .map(|waveform| waveform.refresh_duration(self.frame_period())),
+            _ => None,
+        }
+    }
+
+    /// Check the estimated full update duration against the recommended refresh time of a
+    /// panel.
+    ///
+    /// Returns `Some` with how much longer than `recommended` the estimate is when it exceeds
+    /// it, and `None` when it doesn't. Updates that take longer than recommended may damage the
+    /// panel or leave it faded. Also returns `None` if the duration can't be estimated (see
+    /// `refresh_duration`).
+    pub fn refresh_overrun(&self, recommended: Duration) -> Option<Duration> {
+        self.refresh_duration()
+            .and_then(|duration| duration.checked_sub(recommended))
+            .filter(|overrun| !overrun.is_zero())
+    }
+}
+
 impl<'a> Default for Builder<'a> {
     fn default() -> Self {
         Builder {
-            dummy_line_period: Command::DummyLinePeriod(0x07),
-            gate_line_width: Command::GateLineWidth(0x04),
+            dummy_line_period: 0x07,
+            gate_line_width: 0x04,
             write_vcom: Command::WriteVCOM(0x3C),
             write_lut: None,
+            temperature_luts: &[],
+            write_fast_lut: None,
             data_entry_mode: Command::DataEntryMode(
                 DataEntryMode::IncrementYIncrementX,
                 IncrementAxis::Horizontal,
             ),
+            temperature_sensor: None,
+            border_waveform: None,
+            ram_options: None,
+            update_sequence: UpdateSequence::FULL,
+            gate_voltage: None,
+            source_voltages: None,
+            booster_soft_start: None,
+            busy_timeout_ms: 30_000,
             dimensions: None,
             rotation: Rotation::default(),
         }
@@ -77,27 +414,29 @@ impl<'a> Builder<'a> {
     /// Defaults to 0x07. Corresponds to command 0x3A.
     pub fn dummy_line_period(self, dummy_line_period: u8) -> Self {
         Self {
-            dummy_line_period: Command::DummyLinePeriod(dummy_line_period),
+            dummy_line_period,
             ..self
         }
     }
 
     /// Set the gate line width (TGate).
     ///
-    /// Defaults to 0x04. Corresponds to command 0x3B.
+    /// Defaults to 0x04 (44µs). Corresponds to command 0x3B. Only the low 4 bits are used, they
+    /// select a width from 30µs (0x0) to 208µs (0xF).
     pub fn gate_line_width(self, gate_line_width: u8) -> Self {
         Self {
-            gate_line_width: Command::GateLineWidth(gate_line_width),
+            gate_line_width,
             ..self
         }
     }
 
-    /// Set VCOM register value.
+    /// Set the VCOM DC level.
     ///
-    /// Defaults to 0x3C. Corresponds to command 0x2C.
-    pub fn vcom(self, value: u8) -> Self {
+    /// Defaults to -1.5V (0x3C). Corresponds to command 0x2C. The optimum level for a panel can
+    /// be measured with `Display::sense_vcom`.
+    pub fn vcom(self, vcom: Vcom) -> Self {
         Self {
-            write_vcom: Command::WriteVCOM(value),
+            write_vcom: Command::WriteVCOM(vcom.register()),
             ..self
         }
     }
@@ -109,6 +448,9 @@ impl<'a> Builder<'a> {
     /// There is no default for the lookup table. Corresponds to command 0x32. If not supplied then
     /// the default in the controller is used. Apparently the display manufacturer will normally
     /// supply the LUT values for a particular display batch.
+    ///
+    /// Use `Waveform::to_bytes` to build the table from a typed
+    /// [Waveform](../waveform/struct.Waveform.html).
     pub fn lut(self, lut: &'a [u8]) -> Self {
         Self {
             write_lut: Some(BufCommand::WriteLUT(lut)),
@@ -116,6 +458,37 @@ impl<'a> Builder<'a> {
         }
     }
 
+    /// Set lookup tables to use for different temperature ranges.
+    ///
+    /// Defaults to no temperature dependent LUTs. Before each full update the display writes the
+    /// LUT whose range covers the most recently known temperature. Updates do not read the
+    /// sensor themselves: the temperature is only known after reading the sensor with
+    /// `Display::temperature` or supplying it with `Display::set_temperature`, so call one of
+    /// these whenever the temperature may have changed. If the temperature is unknown or not
+    /// covered by any range then the LUT set with `lut` is used, or without one the controller's
+    /// own LUT is reloaded from OTP.
+    pub fn temperature_luts(self, luts: &'a [TemperatureLut<'a>]) -> Self {
+        Self {
+            temperature_luts: luts,
+            ..self
+        }
+    }
+
+    /// Set lookup table (70 bytes) used for fast updates.
+    ///
+    /// **Note:** The supplied slice must be exactly 70 bytes long.
+    ///
+    /// There is no default for the fast lookup table. It is written to the controller before each
+    /// `Display::update_fast` and the regular LUT is restored before the next full update, by
+    /// writing the LUT set with `lut` or else reloading the controller's own LUT from OTP. If not
+    /// supplied then fast updates use whichever LUT is currently loaded.
+    pub fn fast_lut(self, lut: &'a [u8]) -> Self {
+        Self {
+            write_fast_lut: Some(BufCommand::WriteLUT(lut)),
+            ..self
+        }
+    }
+
     /// Define data entry sequence.
     ///
     /// Defaults to DataEntryMode::IncrementAxis, IncrementAxis::Horizontal. Corresponds to command
@@ -131,6 +504,125 @@ impl<'a> Builder<'a> {
         }
     }
 
+    /// Select the internal or external temperature sensor.
+    ///
+    /// There is no default for this setting. Corresponds to command 0x18. If not supplied then
+    /// the controller uses its power on default, which is the external sensor.
+    pub fn temperature_sensor(self, sensor: TemperatureSensor) -> Self {
+        Self {
+            temperature_sensor: Some(Command::TemperatatSensorSelection(sensor)),
+            ..self
+        }
+    }
+
+    /// Select the waveform driven on the border of the panel.
+    ///
+    /// There is no default for this setting. Corresponds to command 0x3C. If not supplied then
+    /// the controller uses its power on default, `BorderWaveform::HiZ`. Some panels show a grey
+    /// frame around the active area unless the border is driven, for example with
+    /// `BorderWaveform::FollowLut`.
+    pub fn border_waveform(self, border_waveform: BorderWaveform) -> Self {
+        Self {
+            border_waveform: Some(Command::BorderWaveform(border_waveform)),
+            ..self
+        }
+    }
+
+    /// Set how the B/W and Red RAM are used when the display is updated.
+    ///
+    /// There is no default for this setting. Corresponds to command 0x21. If not supplied then
+    /// the controller uses its power on default, `RamOption::Normal` for both. Inverting the B/W
+    /// RAM swaps black and white, bypassing the Red RAM shows the B/W RAM alone. These can be
+    /// changed at runtime with `Display::set_ram_options`.
+    pub fn ram_options(self, black_white: RamOption, red: RamOption) -> Self {
+        Self {
+            ram_options: Some(Command::UpdateDisplayOption1(black_white, red)),
+            ..self
+        }
+    }
+
+    /// Set the display update sequence run by full updates.
+    ///
+    /// Defaults to `UpdateSequence::FULL`. Corresponds to command 0x22, which is sent before
+    /// each update. Fast updates always run `UpdateSequence::FAST`. This can be changed at
+    /// runtime with `Display::set_update_sequence`.
+    ///
+    /// A sequence that loads the LUT from OTP replaces any LUT set with `lut` or
+    /// `temperature_luts`, which is written again before the next update that needs it.
+    pub fn update_sequence(self, update_sequence: UpdateSequence) -> Self {
+        Self {
+            update_sequence,
+            ..self
+        }
+    }
+
+    /// Set the gate driving voltage (VGH).
+    ///
+    /// There is no default for this setting. Corresponds to command 0x03. If not supplied then
+    /// the controller uses its power on default of 20V.
+    pub fn gate_voltage(self, vgh: GateVoltage) -> Self {
+        Self {
+            gate_voltage: Some(Command::GateDrivingVoltage(vgh.register())),
+            ..self
+        }
+    }
+
+    /// Set the source driving voltages (VSH1, VSH2, and VSL).
+    ///
+    /// There is no default for this setting. Corresponds to command 0x04. If not supplied then
+    /// the controller uses its power on defaults of 15V, 5V, and -15V.
+    pub fn source_voltages(
+        self,
+        vsh1: SourceVoltage,
+        vsh2: SourceVoltage,
+        vsl: NegativeSourceVoltage,
+    ) -> Self {
+        Self {
+            source_voltages: Some(Command::SourceDrivingVoltage(
+                vsh1.register(),
+                vsh2.register(),
+                vsl.register(),
+            )),
+            ..self
+        }
+    }
+
+    /// Set the soft start of the booster, which runs `phases` in turn.
+    ///
+    /// There is no default for this setting. Corresponds to command 0x0C. If not supplied then
+    /// the controller uses its power on default (see `BoosterPhase`).
+    pub fn booster_soft_start(self, phases: [BoosterPhase; 3]) -> Self {
+        let mut settings = [0; 3];
+        let mut durations = 0;
+        for (index, phase) in phases.iter().enumerate() {
+            settings[index] = 0x80 | (phase.strength - 1) << 4 | phase.min_off_time;
+            durations |= (phase.duration_ms / 10 - 1) << (2 * index);
+        }
+
+        Self {
+            booster_soft_start: Some(Command::BoosterEnable(
+                settings[0],
+                settings[1],
+                settings[2],
+                durations,
+            )),
+            ..self
+        }
+    }
+
+    /// Set how long to wait for the controller to stop being busy before giving up.
+    ///
+    /// Defaults to 30 seconds, which allows for a full update of a black/white/red panel with the
+    /// LUT built into the controller. A LUT supplied with `lut` may take longer, so compare the
+    /// timeout with `Config::refresh_duration`. If the controller is busy for longer than this a
+    /// timeout error is returned, which usually means the display is disconnected or faulty.
+    pub fn busy_timeout_ms(self, busy_timeout_ms: u32) -> Self {
+        Self {
+            busy_timeout_ms,
+            ..self
+        }
+    }
+
     /// Set the display dimensions.
     ///
     /// There is no default for this setting. The dimensions must be set for the builder to
@@ -172,9 +664,146 @@ impl<'a> Builder<'a> {
             gate_line_width: self.gate_line_width,
             write_vcom: self.write_vcom,
             write_lut: self.write_lut,
+            temperature_luts: self.temperature_luts,
+            write_fast_lut: self.write_fast_lut,
             data_entry_mode: self.data_entry_mode,
-            dimensions: self.dimensions.ok_or_else(|| BuilderError {})?,
+            temperature_sensor: self.temperature_sensor,
+            border_waveform: self.border_waveform,
+            ram_options: self.ram_options,
+            update_sequence: self.update_sequence,
+            gate_voltage: self.gate_voltage,
+            source_voltages: self.source_voltages,
+            booster_soft_start: self.booster_soft_start,
+            busy_timeout_ms: self.busy_timeout_ms,
+            dimensions: self.dimensions.ok_or(BuilderError {})?,
             rotation: self.rotation,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waveform::Phase;
+
+    #[test]
+    fn voltages() {
+        assert_eq!(GateVoltage::from_volts(10.).unwrap().register(), 0x03);
+        assert_eq!(GateVoltage::from_volts(17.).unwrap().register(), 0x11);
+        assert_eq!(GateVoltage::from_volts(20.1).unwrap().register(), 0x17);
+        assert_eq!(GateVoltage::from_volts(21.), None);
+        assert_eq!(GateVoltage::from_volts(9.), None);
+        assert_eq!(GateVoltage(0x0C).volts(), 14.5);
+
+        assert_eq!(SourceVoltage::from_volts(2.4).unwrap().register(), 0x8E);
+        assert_eq!(SourceVoltage::from_volts(5.).unwrap().register(), 0xA8);
+        assert_eq!(SourceVoltage::from_volts(8.8).unwrap().register(), 0xCE);
+        assert_eq!(SourceVoltage::from_volts(8.85).unwrap().register(), 0xCE);
+        assert_eq!(SourceVoltage::from_volts(8.89).unwrap().register(), 0xCE);
+        assert_eq!(SourceVoltage::from_volts(8.9).unwrap().register(), 0x23);
+        assert_eq!(SourceVoltage::from_volts(8.95).unwrap().register(), 0x23);
+        assert_eq!(SourceVoltage::from_volts(9.).unwrap().register(), 0x23);
+        assert_eq!(SourceVoltage::from_volts(15.).unwrap().register(), 0x41);
+        assert_eq!(SourceVoltage::from_volts(17.).unwrap().register(), 0x4B);
+        assert_eq!(SourceVoltage::from_volts(2.), None);
+        assert_eq!(SourceVoltage::from_volts(17.2), None);
+        assert!((SourceVoltage(0xB2).volts() - 6.).abs() < 0.001);
+        assert!((SourceVoltage(0x2D).volts() - 11.).abs() < 0.001);
+
+        assert_eq!(
+            NegativeSourceVoltage::from_volts(-5.).unwrap().register(),
+            0x0A
+        );
+        assert_eq!(
+            NegativeSourceVoltage::from_volts(-15.).unwrap().register(),
+            0x32
+        );
+        assert_eq!(
+            NegativeSourceVoltage::from_volts(-17.).unwrap().register(),
+            0x3A
+        );
+        assert_eq!(NegativeSourceVoltage::from_volts(-4.), None);
+        assert_eq!(NegativeSourceVoltage::from_volts(15.), None);
+        assert_eq!(NegativeSourceVoltage(0x22).volts(), -11.);
+
+        assert_eq!(Vcom::from_volts(-0.2).unwrap().register(), 0x08);
+        assert_eq!(Vcom::from_volts(-1.5).unwrap().register(), 0x3C);
+        assert_eq!(Vcom::from_volts(-3.).unwrap().register(), 0x78);
+        assert_eq!(Vcom::from_volts(-0.1), None);
+        assert_eq!(Vcom::from_volts(1.5), None);
+        assert_eq!(Vcom(0x28).volts(), -1.);
+
+        assert_eq!(VcomSenseDuration::from_secs(1).unwrap().register(), 0x00);
+        assert_eq!(VcomSenseDuration::from_secs(16).unwrap().register(), 0x0F);
+        assert_eq!(VcomSenseDuration::from_secs(10).unwrap().secs(), 10);
+        assert_eq!(VcomSenseDuration::from_secs(0), None);
+        assert_eq!(VcomSenseDuration::from_secs(17), None);
+    }
+
+    #[test]
+    fn booster_soft_start() {
+        let builder = Builder::new().booster_soft_start([
+            BoosterPhase::new(1, 0xB, 40).unwrap(),
+            BoosterPhase::new(2, 0xC, 40).unwrap(),
+            BoosterPhase::new(2, 0x6, 10).unwrap(),
+        ]);
+        match builder.booster_soft_start {
+            Some(Command::BoosterEnable(phase1, phase2, phase3, duration)) => {
+                assert_eq!([phase1, phase2, phase3, duration], [0x8B, 0x9C, 0x96, 0x0F])
+            }
+            _ => panic!("booster soft start not set"),
+        }
+    }
+
+    #[test]
+    fn booster_phase_out_of_range() {
+        assert_eq!(BoosterPhase::new(0, 0x4, 10), None);
+        assert_eq!(BoosterPhase::new(9, 0x4, 10), None);
+        assert_eq!(BoosterPhase::new(8, 0x3, 10), None);
+        assert_eq!(BoosterPhase::new(8, 0x10, 10), None);
+        assert_eq!(BoosterPhase::new(8, 0xF, 15), None);
+        assert_eq!(BoosterPhase::new(8, 0xF, 50), None);
+        assert_eq!(BoosterPhase::new(8, 0xF, 30).unwrap().duration_ms(), 30);
+    }
+
+    #[test]
+    fn refresh_timing() {
+        let lut = Waveform::new()
+            .phase(
+                0,
+                Phase {
+                    durations: [10, 20, 30, 40],
+                    repeat: 4,
+                    ..Phase::EMPTY
+                },
+            )
+            .to_bytes();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 292, cols: 8 })
+            .dummy_line_period(8)
+            .gate_line_width(0x0D)
+            .build()
+            .unwrap();
+        assert_eq!(config.frame_period(), Duration::from_micros(37_500));
+        assert!((config.frame_frequency() - 26.667).abs() < 0.001);
+        assert_eq!(config.refresh_duration(), None);
+
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 292, cols: 8 })
+            .dummy_line_period(8)
+            .gate_line_width(0x0D)
+            .lut(&lut)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.refresh_duration(),
+            Some(Duration::from_millis(18_750))
+        );
+        assert_eq!(config.refresh_overrun(Duration::from_secs(20)), None);
+        assert_eq!(config.refresh_overrun(Duration::from_millis(18_750)), None);
+        assert_eq!(
+            config.refresh_overrun(Duration::from_secs(15)),
+            Some(Duration::from_millis(3_750))
+        );
+    }
+}
@@ -1,10 +1,17 @@
 extern crate libm;
 
-use hal;
+use crate::hal;
 
-use command::{BufCommand, Command, DeepSleepMode};
-use config::Config;
-use interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::asynch::AsyncDisplayInterface;
+use crate::color::Color;
+use crate::command::{BufCommand, Command, DeepSleepMode, RamOption, UpdateSequence};
+use crate::config::{Config, Vcom, VcomSenseDuration};
+use crate::error::Error;
+use crate::interface::{DisplayInterface, ReadInterface};
+use core::ops::Range;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
 
 // Max display resolution is 160x296
 /// The maximum number of rows supported by the controller
@@ -16,6 +23,21 @@ pub const MAX_SOURCE_OUTPUTS: u8 = 160;
 const ANALOG_BLOCK_CONTROL_MAGIC: u8 = 0x54;
 const DIGITAL_BLOCK_CONTROL_MAGIC: u8 = 0x3B;
 
+// Display update sequence that only loads the temperature
+const LOAD_TEMPERATURE_SEQUENCE: UpdateSequence = UpdateSequence::new()
+    .enable_clock()
+    .load_temperature()
+    .disable_clock();
+
+// Display update sequences that power the analog circuits up and down around VCOM sensing
+const ENABLE_ANALOG_SEQUENCE: UpdateSequence = UpdateSequence::new().enable_clock().enable_analog();
+const DISABLE_ANALOG_SEQUENCE: UpdateSequence =
+    UpdateSequence::new().disable_analog().disable_clock();
+
+// Auto write RAM step sizes covering the whole of RAM: 296 rows high, 160 columns wide
+const FULL_STEP_HEIGHT: u8 = 0b110;
+const FULL_STEP_WIDTH: u8 = 0b101;
+
 /// Represents the dimensions of the display.
 pub struct Dimensions {
     /// The number of rows the display has.
@@ -48,68 +70,538 @@ impl Default for Rotation {
     }
 }
 
+/// A rectangular region of the display in the native orientation of the controller.
+///
+/// Since each byte of controller RAM holds 8 horizontally adjacent pixels `x` and `cols` must be
+/// multiples of 8.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    /// The column of the left edge of the region.
+    pub x: u8,
+    /// The row of the top edge of the region.
+    pub y: u16,
+    /// The number of columns in the region.
+    pub cols: u8,
+    /// The number of rows in the region.
+    pub rows: u16,
+}
+
+/// The size of each step of a `Pattern`, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    Px8,
+    Px16,
+    Px32,
+    Px64,
+    Px128,
+}
+
+/// A regular pattern that the controller can fill its RAM with, see `Display::fill_ram`.
+///
+/// Patterns are in the native orientation of the controller and start in the top left corner
+/// with the first colour, then alternate between the two colours every step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pattern {
+    /// The whole of RAM set to a single colour.
+    Solid(Color),
+    /// Squares of `Step` pixels.
+    Checkerboard(Step, Color, Color),
+    /// Stripes `Step` rows high.
+    HorizontalStripes(Step, Color, Color),
+    /// Stripes `Step` columns wide.
+    VerticalStripes(Step, Color, Color),
+}
+
+impl Pattern {
+    /// The auto write commands that fill the B/W and Red RAM with the pattern.
+    fn auto_write_commands(self) -> (Command, Command) {
+        let (height, width, first, second) = match self {
+            Pattern::Solid(color) => (FULL_STEP_HEIGHT, FULL_STEP_WIDTH, color, color),
+            Pattern::Checkerboard(step, first, second) => (step as u8, step as u8, first, second),
+            Pattern::HorizontalStripes(step, first, second) => {
+                (step as u8, FULL_STEP_WIDTH, first, second)
+            }
+            Pattern::VerticalStripes(step, first, second) => {
+                (FULL_STEP_HEIGHT, step as u8, first, second)
+            }
+        };
+        let (first_black, first_red) = ram_bits(first);
+        let (second_black, second_red) = ram_bits(second);
+
+        // A plane that is the same for both colours is filled with a single step
+        let pattern = |first: bool, second: bool| {
+            let (height, width) = if first == second {
+                (FULL_STEP_HEIGHT, FULL_STEP_WIDTH)
+            } else {
+                (height, width)
+            };
+            (first as u8) << 7 | height << 4 | width
+        };
+
+        (
+            Command::AutoWriteBlackPattern(pattern(first_black, second_black)),
+            Command::AutoWriteRedPattern(pattern(first_red, second_red)),
+        )
+    }
+}
+
+/// The bits of the B/W and Red RAM for a pixel of `color`.
+fn ram_bits(color: Color) -> (bool, bool) {
+    match color {
+        Color::Black => (false, false),
+        Color::White => (true, false),
+        Color::Red => (true, true),
+    }
+}
+
+/// The deep sleep modes of the controller.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SleepMode {
+    /// Retain the contents of RAM. After waking the display can be refreshed without sending the
+    /// buffers again.
+    PreserveRAM,
+    /// Discard the contents of RAM for the lowest power consumption. After waking the buffers
+    /// must be sent again.
+    DiscardRAM,
+}
+
+impl From<SleepMode> for DeepSleepMode {
+    fn from(mode: SleepMode) -> Self {
+        match mode {
+            SleepMode::PreserveRAM => DeepSleepMode::PreserveRAM,
+            SleepMode::DiscardRAM => DeepSleepMode::DiscardRAM,
+        }
+    }
+}
+
+/// The LUT most recently loaded into the controller.
+#[derive(Clone, Copy, PartialEq)]
+enum Lut {
+    /// The LUT from the config
+    Default,
+    /// An entry from the temperature LUTs of the config
+    Temperature(usize),
+    /// The fast LUT from the config
+    Fast,
+    /// The controller's own LUT, loaded from OTP
+    Otp,
+}
+
+/// How a LUT that isn't loaded is loaded before an update.
+enum LutLoad<'a> {
+    /// Write it with `WriteLUT`
+    Write(BufCommand<'a>),
+    /// Load it from OTP as part of the update sequence
+    Otp,
+}
+
+/// A RAM window of the byte columns `x_start..=x_end` and rows `y_start..=y_end`.
+#[derive(Clone, Copy)]
+struct Window {
+    x_start: u8,
+    x_end: u8,
+    y_start: u16,
+    y_end: u16,
+}
+
+impl Window {
+    /// Returns the ranges of buffer bytes to write to fill the window, in order, for a display
+    /// `bytes_per_row` bytes wide.
+    ///
+    /// The address counter moves to the start of the next row of the window after the end of
+    /// each row, so each row can be written in turn. When the window spans the whole width of
+    /// the display its rows are contiguous in the buffer and are written at once.
+    fn ranges(self, bytes_per_row: usize) -> impl Iterator<Item = Range<usize>> + Clone {
+        let rows = usize::from(self.y_end - self.y_start) + 1;
+        let chunk = if usize::from(self.x_end - self.x_start) + 1 == bytes_per_row {
+            rows
+        } else {
+            1
+        };
+
+        (0..rows).step_by(chunk).map(move |row| {
+            let start = (usize::from(self.y_start) + row) * bytes_per_row;
+            start + usize::from(self.x_start)
+                ..start + (chunk - 1) * bytes_per_row + usize::from(self.x_end) + 1
+        })
+    }
+
+    /// The commands that set the RAM window to this window and write the bytes of `black`, and
+    /// `red` if supplied, within it.
+    ///
+    /// Each plane is written with a single command, the rows after the first are sent as further
+    /// data for it.
+    fn ram_ops<'b>(
+        self,
+        bytes_per_row: usize,
+        black: &'b [u8],
+        red: Option<&'b [u8]>,
+    ) -> impl Iterator<Item = Op<'b>> + 'b {
+        let set_window = [
+            Op::Command(Command::StartEndXPosition(self.x_start, self.x_end)),
+            Op::Command(Command::StartEndYPosition(self.y_start, self.y_end)),
+        ];
+        let set_address = [
+            Op::Command(Command::XAddress(self.x_start)),
+            Op::Command(Command::YAddress(self.y_start)),
+        ];
+        let ranges = self.ranges(bytes_per_row);
+        let red_ranges = ranges.clone();
+
+        set_window
+            .into_iter()
+            .chain(set_address)
+            .chain(write_ops(ranges, black, BufCommand::WriteBlackData))
+            .chain(red.into_iter().flat_map(move |red| {
+                set_address.into_iter().chain(write_ops(
+                    red_ranges.clone(),
+                    red,
+                    BufCommand::WriteRedData,
+                ))
+            }))
+    }
+}
+
+/// The ops that write `ranges` of `buffer`: `write` with the first range, followed by the rest as
+/// further data.
+fn write_ops<'b>(
+    ranges: impl Iterator<Item = Range<usize>>,
+    buffer: &'b [u8],
+    write: fn(&'b [u8]) -> BufCommand<'b>,
+) -> impl Iterator<Item = Op<'b>> {
+    ranges.enumerate().map(move |(index, range)| {
+        if index == 0 {
+            Op::Buf(write(&buffer[range]))
+        } else {
+            Op::Data(&buffer[range])
+        }
+    })
+}
+
+/// A command sent during an update.
+///
+/// The commands making up an update are built without doing any I/O, so that the blocking and
+/// async paths can share them and only differ in how they're sent.
+#[derive(Clone, Copy)]
+enum Op<'b> {
+    Command(Command),
+    Buf(BufCommand<'b>),
+    /// More data for the previous command
+    Data(&'b [u8]),
+}
+
+impl<'b> Op<'b> {
+    fn execute<I: DisplayInterface>(
+        self,
+        interface: &mut I,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        match self {
+            Op::Command(command) => command.execute(interface),
+            Op::Buf(command) => command.execute(interface),
+            Op::Data(data) => interface.send_data(data),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn execute_async<I: AsyncDisplayInterface>(
+        self,
+        interface: &mut I,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        match self {
+            Op::Command(command) => command.execute_async(interface).await,
+            Op::Buf(command) => command.execute_async(interface).await,
+            Op::Data(data) => interface.send_data(data).await,
+        }
+    }
+}
+
 /// A configured display with a hardware interface.
-pub struct Display<'a, I>
-where
-    I: DisplayInterface,
-{
+pub struct Display<'a, I> {
     interface: I,
     config: Config<'a>,
+    temperature: Option<f32>,
+    lut: Lut,
+    /// A refresh has been started and may not have finished
+    refreshing: bool,
+    /// The controller RAM holds the last frame sent to it
+    ram_valid: bool,
 }
 
-impl<'a, I> Display<'a, I>
+/// A display refresh that has been started but not necessarily finished.
+///
+/// Returned by `Display::start_update`. Refreshing the panel takes several seconds, during which
+/// the program is free to do other work. Poll `is_busy` to find out when the refresh has
+/// finished, or call `finish` to wait for it. If the handle is dropped before then, the next
+/// command sent through the `Display` waits for the refresh to finish first.
+#[must_use = "the refresh should be polled with `is_busy` or completed with `finish`"]
+pub struct Refresh<'d, 'a, I> {
+    display: &'d mut Display<'a, I>,
+}
+
+/// A display whose controller is in deep sleep.
+///
+/// Returned by `deep_sleep` on a `Display` or `GraphicDisplay`. The only thing that can be done
+/// with a sleeping display is to `wake` it, which gives back the display it was created from. If
+/// waking fails the sleeping display is returned with the error, so it can be tried again.
+#[must_use = "a sleeping display must be woken to be used again"]
+pub struct SleepingDisplay<T> {
+    display: T,
+    mode: SleepMode,
+}
+
+/// Error returned when a display could not be put into deep sleep or woken from it.
+///
+/// Holds the display so that it isn't lost: the `Display` or `GraphicDisplay` if entering deep
+/// sleep failed, or the `SleepingDisplay` if waking failed, so waking can be tried again.
+pub struct SleepError<T, SpiE, PinE> {
+    /// The display that could not be put to sleep or woken.
+    pub display: T,
+    /// The error that occurred.
+    pub error: Error<SpiE, PinE>,
+}
+
+impl<T, SpiE, PinE> core::fmt::Debug for SleepError<T, SpiE, PinE>
 where
-    I: DisplayInterface,
+    Error<SpiE, PinE>: core::fmt::Debug,
 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SleepError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, I> Display<'a, I> {
     /// Create a new display instance from a DisplayInterface and Config.
     ///
     /// The `Config` is typically created with `config::Builder`.
     pub fn new(interface: I, config: Config<'a>) -> Self {
-        Self { interface, config }
+        Self {
+            interface,
+            config,
+            temperature: None,
+            lut: Lut::Default,
+            refreshing: false,
+            ram_valid: false,
+        }
+    }
+
+    /// Returns the number of rows the display has.
+    pub fn rows(&self) -> u16 {
+        self.config.dimensions.rows
+    }
+
+    /// Returns the number of columns the display has.
+    pub fn cols(&self) -> u8 {
+        self.config.dimensions.cols
+    }
+
+    /// Returns the rotation the display was configured with.
+    pub fn rotation(&self) -> Rotation {
+        self.config.rotation
+    }
+
+    /// Returns a reference to the interface to the controller.
+    pub fn interface(&self) -> &I {
+        &self.interface
+    }
+
+    /// Returns a mutable reference to the interface to the controller.
+    pub fn interface_mut(&mut self) -> &mut I {
+        &mut self.interface
+    }
+
+    /// Returns whether the controller RAM holds the last frame sent to it.
+    ///
+    /// This is the case after a full update or `fill_ram`, until the controller is reset or woken
+    /// from `SleepMode::DiscardRAM`. Region and fast updates keep it as it was. While it holds,
+    /// `refresh_from_ram` can be used instead of sending the buffers again.
+    pub fn ram_valid(&self) -> bool {
+        self.ram_valid
+    }
+
+    /// Returns the sequence run by full updates and `refresh_from_ram`.
+    pub fn update_sequence(&self) -> UpdateSequence {
+        self.config.update_sequence
+    }
+
+    /// Change the sequence run by full updates and `refresh_from_ram`.
+    ///
+    /// This replaces the sequence set with `Builder::update_sequence`. For example a sequence
+    /// without `disable_analog` keeps the analog block powered between back-to-back updates.
+    /// Fast updates always run `UpdateSequence::FAST`.
+    pub fn set_update_sequence(&mut self, sequence: UpdateSequence) {
+        self.config.update_sequence = sequence;
+    }
+
+    /// The commands sent to initialise the controller, other than the LUT.
+    ///
+    /// Follows Section 9: Typical Operating Sequence from the data sheet.
+    fn init_commands(&self) -> impl Iterator<Item = Command> {
+        let analog = [
+            Command::AnalogBlockControl(ANALOG_BLOCK_CONTROL_MAGIC),
+            Command::DigitalBlockControl(DIGITAL_BLOCK_CONTROL_MAGIC),
+            Command::DriverOutputControl(self.config.dimensions.rows, 0x00),
+        ];
+        let timing = [
+            Command::DummyLinePeriod(self.config.dummy_line_period),
+            Command::GateLineWidth(self.config.gate_line_width),
+            self.config.write_vcom,
+        ];
+        let ram = [
+            self.config.data_entry_mode,
+            Command::StartEndXPosition(0, self.cols() / 8 - 1),
+            Command::StartEndYPosition(0, self.rows() - 1),
+        ];
+
+        analog
+            .into_iter()
+            .chain(self.config.gate_voltage)
+            .chain(self.config.source_voltages)
+            .chain(self.config.booster_soft_start)
+            .chain(timing)
+            .chain(self.config.border_waveform)
+            .chain(self.config.ram_options)
+            .chain(self.config.temperature_sensor)
+            .chain(ram)
+    }
+
+    /// The number of bytes in a buffer holding the whole display.
+    fn buffer_len(&self) -> usize {
+        libm::ceilf((self.rows() * self.cols() as u16) as f32 / 8.) as usize
+    }
+
+    /// Check the buffers passed to an update hold the whole display.
+    fn check_buffers<SpiE, PinE>(&self, buffers: &[&[u8]]) -> Result<(), Error<SpiE, PinE>> {
+        if buffers
+            .iter()
+            .all(|buffer| buffer.len() >= self.buffer_len())
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidBufferLength)
+        }
+    }
+
+    /// The number of bytes in each row of a buffer.
+    fn bytes_per_row(&self) -> usize {
+        usize::from(self.cols() / 8)
+    }
+
+    /// Returns the RAM window covering the whole display.
+    fn full_window(&self) -> Window {
+        Window {
+            x_start: 0,
+            x_end: self.cols() / 8 - 1,
+            y_start: 0,
+            y_end: self.rows() - 1,
+        }
+    }
+
+    /// Returns the RAM window covering `region`, or `None` if the region is empty.
+    ///
+    /// Returns `Error::InvalidRegion` if the region is not byte aligned or does not fit within
+    /// the display.
+    fn region_window<SpiE, PinE>(
+        &self,
+        region: Region,
+    ) -> Result<Option<Window>, Error<SpiE, PinE>> {
+        let aligned = region.x % 8 == 0 && region.cols % 8 == 0;
+        let within = u16::from(region.x) + u16::from(region.cols) <= u16::from(self.cols())
+            && region
+                .y
+                .checked_add(region.rows)
+                .is_some_and(|y_end| y_end <= self.rows());
+        if !aligned || !within {
+            return Err(Error::InvalidRegion);
+        }
+        if region.cols == 0 || region.rows == 0 {
+            return Ok(None);
+        }
+
+        let x_start = region.x / 8;
+        Ok(Some(Window {
+            x_start,
+            x_end: x_start + region.cols / 8 - 1,
+            y_start: region.y,
+            y_end: region.y + (region.rows - 1),
+        }))
+    }
+
+    /// The LUT for the most recently known temperature, used by full updates.
+    fn full_update_lut(&self) -> Lut {
+        self.temperature
+            .and_then(|celsius| {
+                self.config
+                    .temperature_luts
+                    .iter()
+                    .position(|entry| entry.covers(celsius))
+            })
+            .map_or(self.default_lut(), Lut::Temperature)
+    }
+
+    /// The LUT used by full updates when no temperature LUT applies: the one from the config,
+    /// or the controller's own if the config doesn't have one.
+    fn default_lut(&self) -> Lut {
+        if self.config.write_lut.is_some() {
+            Lut::Default
+        } else {
+            Lut::Otp
+        }
+    }
+
+    /// How to load `lut`, if it's not already loaded.
+    ///
+    /// Without a fast LUT in the config fast updates use whichever LUT is loaded.
+    fn pending_lut(&self, lut: Lut) -> Option<LutLoad<'a>> {
+        if lut == self.lut {
+            return None;
+        }
+
+        match lut {
+            Lut::Default => Some(self.config.write_lut.map_or(LutLoad::Otp, LutLoad::Write)),
+            Lut::Temperature(index) => Some(LutLoad::Write(BufCommand::WriteLUT(
+                self.config.temperature_luts[index].lut,
+            ))),
+            Lut::Fast => self.config.write_fast_lut.map(LutLoad::Write),
+            Lut::Otp => Some(LutLoad::Otp),
+        }
     }
+}
 
+impl<'a, I> Display<'a, I>
+where
+    I: DisplayInterface,
+{
     /// Perform a hardware reset followed by software reset.
     ///
-    /// This will wake a controller that has previously entered deep sleep.
+    /// This must be called to initialise the controller before the display is first used.
     pub fn reset<D: hal::blocking::delay::DelayMs<u8>>(
         &mut self,
         delay: &mut D,
-    ) -> Result<(), I::Error> {
-        self.interface.reset(delay);
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.interface.reset(delay)?;
+        // The hardware reset aborts any refresh in progress
+        self.refreshing = false;
+        self.ram_valid = false;
         Command::SoftReset.execute(&mut self.interface)?;
-        self.interface.busy_wait();
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
 
         self.init()
     }
 
     /// Initialise the controller according to Section 9: Typical Operating Sequence
     /// from the data sheet
-    fn init(&mut self) -> Result<(), I::Error> {
-        Command::AnalogBlockControl(ANALOG_BLOCK_CONTROL_MAGIC).execute(&mut self.interface)?;
-        Command::DigitalBlockControl(DIGITAL_BLOCK_CONTROL_MAGIC).execute(&mut self.interface)?;
-
-        Command::DriverOutputControl(self.config.dimensions.rows, 0x00)
-            .execute(&mut self.interface)?;
-
-        self.config.dummy_line_period.execute(&mut self.interface)?;
-        self.config.gate_line_width.execute(&mut self.interface)?;
-
-        // Command::GateDrivingVoltage(0b10000 | 0b0001);
-        // Command::SourceDrivingVoltage(0x2D, 0xB2, 0x22).execute(&mut self.interface)?;
-        self.config.write_vcom.execute(&mut self.interface)?;
-
-        // POR is HiZ. Need pull from config
-        // Command::BorderWaveform(u8).execute(&mut self.interface)?;
+    fn init(&mut self) -> Result<(), Error<I::SpiError, I::PinError>> {
+        for command in self.init_commands() {
+            command.execute(&mut self.interface)?;
+        }
 
-        if let Some(ref write_lut) = self.config.write_lut {
+        if let Some(write_lut) = self.config.write_lut {
             write_lut.execute(&mut self.interface)?;
         }
-
-        self.config.data_entry_mode.execute(&mut self.interface)?;
-
-        let end = self.config.dimensions.cols / 8 - 1;
-        Command::StartEndXPosition(0, end).execute(&mut self.interface)?;
-        Command::StartEndYPosition(0, self.config.dimensions.rows).execute(&mut self.interface)?;
+        self.lut = self.default_lut();
 
         Ok(())
     }
@@ -117,57 +609,1898 @@ where
     /// Update the display by writing the supplied B/W and Red buffers to the controller.
     ///
     /// This method will write the two buffers to the controller then initiate the update
-    /// display command. Currently it will busy wait until the update has completed.
+    /// display command. It will busy wait until the update has completed, use `start_update`
+    /// to do other work in the meantime.
+    ///
+    /// If the config has temperature LUTs the one for the most recently known temperature is
+    /// written before the update is started.
+    ///
+    /// Returns `Error::InvalidBufferLength` if either buffer is smaller than the display.
     pub fn update<D: hal::blocking::delay::DelayMs<u8>>(
         &mut self,
         black: &[u8],
         red: &[u8],
         delay: &mut D,
-    ) -> Result<(), I::Error> {
-        // Write the B/W RAM
-        let buf_limit = libm::ceilf((self.rows() * self.cols() as u16) as f32 / 8.) as usize;
-        Command::XAddress(0).execute(&mut self.interface)?;
-        Command::YAddress(0).execute(&mut self.interface)?;
-        BufCommand::WriteBlackData(&black[..buf_limit]).execute(&mut self.interface)?;
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.start_update(black, red, delay)?.finish(delay)
+    }
+
+    /// Start updating the display from the supplied B/W and Red buffers without waiting for the
+    /// refresh to finish.
+    ///
+    /// The buffers are written to the controller and the update is initiated, the same as
+    /// `update`. The returned `Refresh` can be polled to find out when the refresh has finished.
+    pub fn start_update<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        black: &[u8],
+        red: &[u8],
+        delay: &mut D,
+    ) -> Result<Refresh<'_, 'a, I>, Error<I::SpiError, I::PinError>> {
+        self.check_buffers(&[black, red])?;
+        self.finish_refresh(delay)?;
+
+        // A previous region update may have changed the window, so it's always set
+        self.ram_valid = false;
+        for op in self
+            .full_window()
+            .ram_ops(self.bytes_per_row(), black, Some(red))
+        {
+            op.execute(&mut self.interface)?;
+        }
+        self.ram_valid = true;
+
+        let sequence = self.load_lut(self.full_update_lut(), self.config.update_sequence)?;
+        self.start_refresh(sequence, delay)?;
+
+        Ok(Refresh { display: self })
+    }
+
+    /// Update a region of the display from the supplied B/W and Red buffers.
+    ///
+    /// The buffers hold the whole display, the same as `update`, but only the bytes within
+    /// `region` are written to the controller. This is much quicker than transferring the whole
+    /// buffer when only a small part of the display has changed. The update itself still
+    /// refreshes the whole panel.
+    ///
+    /// This assumes the default data entry mode (`IncrementYIncrementX`, `Horizontal`).
+    ///
+    /// Returns `Error::InvalidRegion` if the region is not byte aligned or does not fit within
+    /// the display, and `Error::InvalidBufferLength` if either buffer is smaller than the
+    /// display.
+    pub fn update_region<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        region: Region,
+        black: &[u8],
+        red: &[u8],
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let window = self.region_window(region)?;
+        self.check_buffers(&[black, red])?;
+        let window = match window {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+        self.finish_refresh(delay)?;
+
+        // RAM outside the region is left as is, so it only holds the frame if it did before.
+        // Should a write fail part way through it holds neither.
+        let ram_valid = core::mem::replace(&mut self.ram_valid, false);
+        for op in window.ram_ops(self.bytes_per_row(), black, Some(red)) {
+            op.execute(&mut self.interface)?;
+        }
+        self.ram_valid = ram_valid;
+
+        let sequence = self.load_lut(self.full_update_lut(), self.config.update_sequence)?;
+        self.refresh(sequence, delay)
+    }
+
+    /// Quickly update the display from the supplied B/W buffer.
+    ///
+    /// This writes only the B/W RAM then runs the display mode 2 update sequence using the fast
+    /// LUT from the config (see `Builder::fast_lut`). It avoids the flashing of a full update so
+    /// is suited to frequently changing monochrome content. The red RAM is left as is. Since
+    /// fast updates can leave ghosting behind, a full `update` should be done from time to time.
+    ///
+    /// Returns `Error::InvalidBufferLength` if the buffer is smaller than the display.
+    pub fn update_fast<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        black: &[u8],
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.check_buffers(&[black])?;
+        self.finish_refresh(delay)?;
+
+        // The red RAM is left as is, so RAM only holds the frame if it did before
+        let ram_valid = core::mem::replace(&mut self.ram_valid, false);
+        for op in self
+            .full_window()
+            .ram_ops(self.bytes_per_row(), black, None)
+        {
+            op.execute(&mut self.interface)?;
+        }
+        self.ram_valid = ram_valid;
+
+        let sequence = self.load_lut(Lut::Fast, UpdateSequence::FAST)?;
+        self.refresh(sequence, delay)
+    }
+
+    /// Refresh the display from the frame already held in the controller RAM.
+    ///
+    /// This runs the full update sequence without sending the buffers, for example to redraw the
+    /// panel after waking from `SleepMode::PreserveRAM`.
+    ///
+    /// Returns `Error::StaleRam` if the RAM does not hold the last frame (see `ram_valid`).
+    pub fn refresh_from_ram<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        if !self.ram_valid {
+            return Err(Error::StaleRam);
+        }
+        self.finish_refresh(delay)?;
+        let sequence = self.load_lut(self.full_update_lut(), self.config.update_sequence)?;
+        self.refresh(sequence, delay)
+    }
+
+    /// Fill the controller RAM with a single colour.
+    ///
+    /// See `fill_ram`.
+    pub fn clear_ram<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        color: Color,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.fill_ram(Pattern::Solid(color), delay)
+    }
+
+    /// Fill the controller RAM with a regular pattern.
+    ///
+    /// The controller generates the pattern itself with the auto write RAM commands, so this is
+    /// much quicker than sending buffers. The panel is not refreshed, follow this with
+    /// `refresh_from_ram` to show the pattern.
+    ///
+    /// The whole of RAM is filled, regardless of the dimensions of the display. The buffers of a
+    /// `GraphicDisplay` are not changed, use `GraphicDisplay::clear_ram` to keep them in step.
+    pub fn fill_ram<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        pattern: Pattern,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh(delay)?;
+        let (black, red) = pattern.auto_write_commands();
 
-        // Write the Red RAM
-        Command::XAddress(0).execute(&mut self.interface)?;
-        Command::YAddress(0).execute(&mut self.interface)?;
-        BufCommand::WriteRedData(&red[..buf_limit]).execute(&mut self.interface)?;
+        // BUSY is high while each plane is written
+        self.ram_valid = false;
+        black.execute(&mut self.interface)?;
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
+        red.execute(&mut self.interface)?;
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
+        self.ram_valid = true;
 
+        Ok(())
+    }
+
+    /// Activate the display update sequence and wait for it to complete.
+    fn refresh<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        sequence: UpdateSequence,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.start_refresh(sequence, delay)?;
+        self.finish_refresh(delay)
+    }
+
+    /// Activate the display update sequence without waiting for it to complete.
+    fn start_refresh<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        sequence: UpdateSequence,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
         // Kick off the display update
-        Command::UpdateDisplayOption2(0xC7).execute(&mut self.interface)?;
+        Command::UpdateDisplayOption2(sequence).execute(&mut self.interface)?;
         Command::UpdateDisplay.execute(&mut self.interface)?;
+        // The written LUT is replaced if the sequence loads one from OTP
+        if sequence.loads_lut() {
+            self.lut = Lut::Otp;
+        }
+        // Give the controller time to raise BUSY
         delay.delay_ms(50);
-        // TODO: We don't really need to wait here... the program can go off and do other things
-        // and only busy wait if it wants to talk to the display again. Could possibly treat
-        // the interface like a smart pointer in which deref would wait until it's not
-        // busy.
-        self.interface.busy_wait();
+        self.refreshing = true;
+
+        Ok(())
+    }
+
+    /// Wait for a refresh that has been started to finish, if there is one.
+    fn finish_refresh<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        if self.refreshing {
+            self.interface
+                .busy_wait(delay, self.config.busy_timeout_ms)?;
+            self.refreshing = false;
+        }
+
+        Ok(())
+    }
+
+    /// Supply the temperature of the panel in degrees Celsius.
+    ///
+    /// The temperature is written to the temperature register of the controller and is used to
+    /// select a LUT on the next update. Use this when the temperature is measured by the host
+    /// rather than a sensor attached to the controller.
+    pub fn set_temperature<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        celsius: f32,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh(delay)?;
+        Command::WriteTemperatureSensor(temperature_to_register(celsius))
+            .execute(&mut self.interface)?;
+        self.temperature = Some(celsius);
 
         Ok(())
     }
 
+    /// Change how the B/W and Red RAM are used by subsequent updates.
+    ///
+    /// This replaces the options set with `Builder::ram_options`, including after a reset. For
+    /// example invert the B/W RAM for a night mode, or bypass the Red RAM for a black and white
+    /// only update, without changing the buffers. The panel is not refreshed, the options apply
+    /// from the next update or `refresh_from_ram`.
+    pub fn set_ram_options<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        black_white: RamOption,
+        red: RamOption,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh(delay)?;
+        let ram_options = Command::UpdateDisplayOption1(black_white, red);
+        self.config.ram_options = Some(ram_options);
+        ram_options.execute(&mut self.interface)
+    }
+
+    /// Load `lut` ahead of an update run with `sequence`, if it's not already loaded.
+    ///
+    /// Returns the sequence to run, which also loads the LUT if it comes from OTP.
+    fn load_lut(
+        &mut self,
+        lut: Lut,
+        sequence: UpdateSequence,
+    ) -> Result<UpdateSequence, Error<I::SpiError, I::PinError>> {
+        let sequence = match self.pending_lut(lut) {
+            Some(LutLoad::Write(write_lut)) => {
+                write_lut.execute(&mut self.interface)?;
+                sequence
+            }
+            Some(LutLoad::Otp) => sequence.load_lut(),
+            None => return Ok(sequence),
+        };
+        self.lut = lut;
+
+        Ok(sequence)
+    }
+
     /// Enter deep sleep mode.
     ///
-    /// This puts the display controller into a low power mode. `reset` must be called to wake it
-    /// from sleep.
-    pub fn deep_sleep(&mut self) -> Result<(), I::Error> {
-        Command::DeepSleepMode(DeepSleepMode::PreserveRAM).execute(&mut self.interface)
+    /// This puts the display controller into a low power mode. The display is consumed and can
+    /// only be used again after waking it with `SleepingDisplay::wake`. With
+    /// `SleepMode::PreserveRAM` the frame is kept, so after waking the display can be refreshed
+    /// with `refresh_from_ram`. With `SleepMode::DiscardRAM` the buffers must be sent again.
+    ///
+    /// If the controller can't be put to sleep the display is returned with the error.
+    #[allow(clippy::result_large_err)] // The display is returned with the error
+    pub fn deep_sleep<D: hal::blocking::delay::DelayMs<u8>>(
+        mut self,
+        mode: SleepMode,
+        delay: &mut D,
+    ) -> Result<SleepingDisplay<Self>, SleepError<Self, I::SpiError, I::PinError>> {
+        match self.enter_deep_sleep(mode, delay) {
+            Ok(()) => Ok(SleepingDisplay::new(self, mode)),
+            Err(error) => Err(SleepError {
+                display: self,
+                error,
+            }),
+        }
     }
 
-    /// Returns the number of rows the display has.
-    pub fn rows(&self) -> u16 {
-        self.config.dimensions.rows
+    /// Put the controller into deep sleep.
+    pub(crate) fn enter_deep_sleep<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        mode: SleepMode,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh(delay)?;
+        Command::DeepSleepMode(mode.into()).execute(&mut self.interface)
     }
 
-    /// Returns the number of columns the display has.
-    pub fn cols(&self) -> u8 {
-        self.config.dimensions.cols
+    /// Wake the controller from deep sleep in `mode`.
+    pub(crate) fn wake_from_sleep<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        mode: SleepMode,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let ram_valid = self.ram_valid && mode == SleepMode::PreserveRAM;
+        let result = self.reset(delay);
+        // Neither the hardware nor the software reset clear RAM, even if the reset failed
+        self.ram_valid = ram_valid;
+
+        result
     }
+}
 
-    /// Returns the rotation the display was configured with.
-    pub fn rotation(&self) -> Rotation {
-        self.config.rotation
+impl<T> SleepingDisplay<T> {
+    /// Wrap a display whose controller has just entered deep sleep in `mode`.
+    pub(crate) fn new(display: T, mode: SleepMode) -> Self {
+        Self { display, mode }
+    }
+
+    /// Returns the mode the controller is sleeping in.
+    pub fn mode(&self) -> SleepMode {
+        self.mode
+    }
+
+    /// Returns the display, still asleep, and the mode it is sleeping in.
+    pub(crate) fn into_inner(self) -> (T, SleepMode) {
+        (self.display, self.mode)
+    }
+}
+
+impl<'a, I> SleepingDisplay<Display<'a, I>>
+where
+    I: DisplayInterface,
+{
+    /// Wake the controller from deep sleep.
+    ///
+    /// The controller is reset and initialised, the same as `Display::reset`. If it slept in
+    /// `SleepMode::PreserveRAM` the RAM still holds the last frame, see
+    /// `Display::refresh_from_ram`.
+    ///
+    /// If the controller can't be woken the sleeping display is returned with the error.
+    #[allow(clippy::result_large_err)] // The display is returned with the error
+    pub fn wake<D: hal::blocking::delay::DelayMs<u8>>(
+        self,
+        delay: &mut D,
+    ) -> Result<Display<'a, I>, SleepError<Self, I::SpiError, I::PinError>> {
+        let (mut display, mode) = self.into_inner();
+        match display.wake_from_sleep(mode, delay) {
+            Ok(()) => Ok(display),
+            Err(error) => Err(SleepError {
+                display: SleepingDisplay::new(display, mode),
+                error,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, I> SleepingDisplay<Display<'a, I>>
+where
+    I: AsyncDisplayInterface,
+{
+    /// Wake the controller from deep sleep, without blocking.
+    ///
+    /// This is the async counterpart of `wake`.
+    pub async fn wake_async<D: DelayNs>(
+        self,
+        delay: &mut D,
+    ) -> Result<Display<'a, I>, SleepError<Self, I::SpiError, I::PinError>> {
+        let (mut display, mode) = self.into_inner();
+        match display.wake_from_sleep_async(mode, delay).await {
+            Ok(()) => Ok(display),
+            Err(error) => Err(SleepError {
+                display: SleepingDisplay::new(display, mode),
+                error,
+            }),
+        }
+    }
+}
+
+impl<'d, 'a, I> Refresh<'d, 'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Returns whether the controller is still refreshing the panel.
+    pub fn is_busy(&mut self) -> Result<bool, Error<I::SpiError, I::PinError>> {
+        let busy = self.display.interface.is_busy()?;
+        if !busy {
+            self.display.refreshing = false;
+        }
+
+        Ok(busy)
+    }
+
+    /// Wait for the refresh to finish.
+    ///
+    /// Returns an error if the controller is still busy after the timeout from the config.
+    pub fn finish<D: hal::blocking::delay::DelayMs<u8>>(
+        self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.display.finish_refresh(delay)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, I> Display<'a, I>
+where
+    I: AsyncDisplayInterface,
+{
+    /// Perform a hardware reset followed by software reset, without blocking.
+    ///
+    /// This is the async counterpart of `reset`.
+    pub async fn reset_async<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.interface.reset(delay).await?;
+        self.refreshing = false;
+        self.ram_valid = false;
+        Command::SoftReset
+            .execute_async(&mut self.interface)
+            .await?;
+        self.wait_idle_async().await?;
+
+        for command in self.init_commands() {
+            command.execute_async(&mut self.interface).await?;
+        }
+        if let Some(write_lut) = self.config.write_lut {
+            write_lut.execute_async(&mut self.interface).await?;
+        }
+        self.lut = self.default_lut();
+
+        Ok(())
+    }
+
+    /// Update the display from the supplied B/W and Red buffers, without blocking.
+    ///
+    /// This is the async counterpart of `update`. The returned future completes when the
+    /// controller signals that the refresh has finished.
+    pub async fn update_async<D: DelayNs>(
+        &mut self,
+        black: &[u8],
+        red: &[u8],
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.check_buffers(&[black, red])?;
+        self.finish_refresh_async().await?;
+
+        self.ram_valid = false;
+        for op in self
+            .full_window()
+            .ram_ops(self.bytes_per_row(), black, Some(red))
+        {
+            op.execute_async(&mut self.interface).await?;
+        }
+        self.ram_valid = true;
+
+        let sequence = self
+            .load_lut_async(self.full_update_lut(), self.config.update_sequence)
+            .await?;
+        self.refresh_async(sequence, delay).await
+    }
+
+    /// Update a region of the display from the supplied B/W and Red buffers, without blocking.
+    ///
+    /// This is the async counterpart of `update_region`, with the same requirements of `region`.
+    pub async fn update_region_async<D: DelayNs>(
+        &mut self,
+        region: Region,
+        black: &[u8],
+        red: &[u8],
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let window = self.region_window(region)?;
+        self.check_buffers(&[black, red])?;
+        let window = match window {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+        self.finish_refresh_async().await?;
+
+        let ram_valid = core::mem::replace(&mut self.ram_valid, false);
+        for op in window.ram_ops(self.bytes_per_row(), black, Some(red)) {
+            op.execute_async(&mut self.interface).await?;
+        }
+        self.ram_valid = ram_valid;
+
+        let sequence = self
+            .load_lut_async(self.full_update_lut(), self.config.update_sequence)
+            .await?;
+        self.refresh_async(sequence, delay).await
+    }
+
+    /// Quickly update the display from the supplied B/W buffer, without blocking.
+    ///
+    /// This is the async counterpart of `update_fast`.
+    pub async fn update_fast_async<D: DelayNs>(
+        &mut self,
+        black: &[u8],
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.check_buffers(&[black])?;
+        self.finish_refresh_async().await?;
+
+        let ram_valid = core::mem::replace(&mut self.ram_valid, false);
+        for op in self
+            .full_window()
+            .ram_ops(self.bytes_per_row(), black, None)
+        {
+            op.execute_async(&mut self.interface).await?;
+        }
+        self.ram_valid = ram_valid;
+
+        let sequence = self.load_lut_async(Lut::Fast, UpdateSequence::FAST).await?;
+        self.refresh_async(sequence, delay).await
+    }
+
+    /// Refresh the display from the frame already held in the controller RAM, without blocking.
+    ///
+    /// This is the async counterpart of `refresh_from_ram`.
+    pub async fn refresh_from_ram_async<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        if !self.ram_valid {
+            return Err(Error::StaleRam);
+        }
+        self.finish_refresh_async().await?;
+        let sequence = self
+            .load_lut_async(self.full_update_lut(), self.config.update_sequence)
+            .await?;
+        self.refresh_async(sequence, delay).await
+    }
+
+    /// Fill the controller RAM with a single colour, without blocking.
+    ///
+    /// This is the async counterpart of `clear_ram`.
+    pub async fn clear_ram_async(
+        &mut self,
+        color: Color,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.fill_ram_async(Pattern::Solid(color)).await
+    }
+
+    /// Fill the controller RAM with a regular pattern, without blocking.
+    ///
+    /// This is the async counterpart of `fill_ram`.
+    pub async fn fill_ram_async(
+        &mut self,
+        pattern: Pattern,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh_async().await?;
+        let (black, red) = pattern.auto_write_commands();
+
+        self.ram_valid = false;
+        black.execute_async(&mut self.interface).await?;
+        self.wait_idle_async().await?;
+        red.execute_async(&mut self.interface).await?;
+        self.wait_idle_async().await?;
+        self.ram_valid = true;
+
+        Ok(())
+    }
+
+    /// Supply the temperature of the panel in degrees Celsius.
+    ///
+    /// This is the async counterpart of `set_temperature`.
+    pub async fn set_temperature_async(
+        &mut self,
+        celsius: f32,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh_async().await?;
+        Command::WriteTemperatureSensor(temperature_to_register(celsius))
+            .execute_async(&mut self.interface)
+            .await?;
+        self.temperature = Some(celsius);
+
+        Ok(())
+    }
+
+    /// Change how the B/W and Red RAM are used by subsequent updates, without blocking.
+    ///
+    /// This is the async counterpart of `set_ram_options`.
+    pub async fn set_ram_options_async(
+        &mut self,
+        black_white: RamOption,
+        red: RamOption,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh_async().await?;
+        let ram_options = Command::UpdateDisplayOption1(black_white, red);
+        self.config.ram_options = Some(ram_options);
+        ram_options.execute_async(&mut self.interface).await
+    }
+
+    /// Enter deep sleep mode.
+    ///
+    /// This is the async counterpart of `deep_sleep`.
+    pub async fn deep_sleep_async(
+        mut self,
+        mode: SleepMode,
+    ) -> Result<SleepingDisplay<Self>, SleepError<Self, I::SpiError, I::PinError>> {
+        match self.enter_deep_sleep_async(mode).await {
+            Ok(()) => Ok(SleepingDisplay::new(self, mode)),
+            Err(error) => Err(SleepError {
+                display: self,
+                error,
+            }),
+        }
+    }
+
+    /// Put the controller into deep sleep, without blocking.
+    pub(crate) async fn enter_deep_sleep_async(
+        &mut self,
+        mode: SleepMode,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.finish_refresh_async().await?;
+        Command::DeepSleepMode(mode.into())
+            .execute_async(&mut self.interface)
+            .await
+    }
+
+    /// Wake the controller from deep sleep in `mode`, without blocking.
+    pub(crate) async fn wake_from_sleep_async<D: DelayNs>(
+        &mut self,
+        mode: SleepMode,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        let ram_valid = self.ram_valid && mode == SleepMode::PreserveRAM;
+        let result = self.reset_async(delay).await;
+        self.ram_valid = ram_valid;
+
+        result
+    }
+
+    /// Await the controller lowering BUSY.
+    ///
+    /// The controller is marked as refreshing until BUSY is low, so if the future is dropped
+    /// partway through the next call waits for it first.
+    async fn wait_idle_async(&mut self) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.refreshing = true;
+        self.finish_refresh_async().await
+    }
+
+    /// Await a refresh started with `start_update` finishing, if there is one.
+    async fn finish_refresh_async(&mut self) -> Result<(), Error<I::SpiError, I::PinError>> {
+        if self.refreshing {
+            self.interface.busy_wait().await?;
+            self.refreshing = false;
+        }
+
+        Ok(())
+    }
+
+    /// Load `lut` ahead of an update run with `sequence`, if it's not already loaded, without
+    /// blocking.
+    async fn load_lut_async(
+        &mut self,
+        lut: Lut,
+        sequence: UpdateSequence,
+    ) -> Result<UpdateSequence, Error<I::SpiError, I::PinError>> {
+        let sequence = match self.pending_lut(lut) {
+            Some(LutLoad::Write(write_lut)) => {
+                write_lut.execute_async(&mut self.interface).await?;
+                sequence
+            }
+            Some(LutLoad::Otp) => sequence.load_lut(),
+            None => return Ok(sequence),
+        };
+        self.lut = lut;
+
+        Ok(sequence)
+    }
+
+    /// Activate the display update sequence and await its completion.
+    async fn refresh_async<D: DelayNs>(
+        &mut self,
+        sequence: UpdateSequence,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        Command::UpdateDisplayOption2(sequence)
+            .execute_async(&mut self.interface)
+            .await?;
+        Command::UpdateDisplay
+            .execute_async(&mut self.interface)
+            .await?;
+        if sequence.loads_lut() {
+            self.lut = Lut::Otp;
+        }
+        // Mark the refresh as started before awaiting, in case the future is dropped
+        self.refreshing = true;
+        // Give the controller time to raise BUSY
+        delay.delay_ms(50).await;
+        self.finish_refresh_async().await
+    }
+}
+
+impl<'a, I> Display<'a, I>
+where
+    I: ReadInterface,
+{
+    /// Measure the temperature of the panel in degrees Celsius.
+    ///
+    /// This instructs the controller to load the temperature register from the selected sensor
+    /// (see `Builder::temperature_sensor`), waits for it to finish, then reads the register back.
+    /// The register has a resolution of 1/16 degree. The measured temperature is used to select
+    /// a LUT on the next update.
+    ///
+    /// Updates never read the sensor themselves, as not every interface can read from the
+    /// controller. Call this before an update for the temperature LUTs (see
+    /// `Builder::temperature_luts`) to follow the temperature of the panel.
+    pub fn temperature<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<f32, Error<I::SpiError, I::PinError>> {
+        self.finish_refresh(delay)?;
+        Command::UpdateDisplayOption2(LOAD_TEMPERATURE_SEQUENCE).execute(&mut self.interface)?;
+        Command::UpdateDisplay.execute(&mut self.interface)?;
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
+
+        let mut buf = [0u8; 2];
+        Command::ReadTemperatureSensor.read(&mut self.interface, &mut buf)?;
+
+        let celsius = temperature_from_register(u16::from_be_bytes(buf) >> 4);
+        self.temperature = Some(celsius);
+
+        Ok(celsius)
+    }
+
+    /// Measure the optimum VCOM DC level of the panel.
+    ///
+    /// The controller senses VCOM for `duration`, then the sensed value is read back. If `apply`
+    /// is true it replaces the VCOM set with `Builder::vcom` and is used from then on, including
+    /// after a reset. Otherwise the configured VCOM is restored.
+    ///
+    /// The busy timeout (see `Builder::busy_timeout_ms`) must be longer than the duration.
+    pub fn sense_vcom<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        duration: VcomSenseDuration,
+        apply: bool,
+        delay: &mut D,
+    ) -> Result<Vcom, Error<I::SpiError, I::PinError>> {
+        self.finish_refresh(delay)?;
+        Command::VCOMSenseDuration(duration.register()).execute(&mut self.interface)?;
+
+        Command::UpdateDisplayOption2(ENABLE_ANALOG_SEQUENCE).execute(&mut self.interface)?;
+        Command::UpdateDisplay.execute(&mut self.interface)?;
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
+
+        Command::EnterVCOMSensing.execute(&mut self.interface)?;
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
+
+        // The sensed value is loaded into the VCOM register, which is the second byte of the
+        // display option
+        let mut buf = [0u8; 2];
+        Command::ReadDisplayOption.read(&mut self.interface, &mut buf)?;
+        let vcom = Vcom::from_register(buf[1]);
+
+        Command::UpdateDisplayOption2(DISABLE_ANALOG_SEQUENCE).execute(&mut self.interface)?;
+        Command::UpdateDisplay.execute(&mut self.interface)?;
+        self.interface
+            .busy_wait(delay, self.config.busy_timeout_ms)?;
+
+        if apply {
+            self.config.write_vcom = Command::WriteVCOM(vcom.register());
+        }
+        self.config.write_vcom.execute(&mut self.interface)?;
+
+        Ok(vcom)
+    }
+}
+
+/// Convert a 12-bit two's complement temperature register value to degrees Celsius.
+fn temperature_from_register(value: u16) -> f32 {
+    // Shift the sign bit up to bit 15 then back down to sign extend
+    let value = ((value << 4) as i16) >> 4;
+    f32::from(value) / 16.
+}
+
+/// Convert degrees Celsius to a 12-bit two's complement temperature register value.
+fn temperature_to_register(celsius: f32) -> u16 {
+    let value = (celsius.clamp(-128., 127.9375) * 16.) as i16;
+    value as u16 & 0xFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{BorderLut, BorderWaveform, TemperatureSensor};
+    use crate::config::{GateVoltage, NegativeSourceVoltage, SourceVoltage, TemperatureLut};
+    use crate::emulator::Emulator;
+    use crate::{Builder, Color};
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    struct MockDelay {}
+
+    impl hal::blocking::delay::DelayMs<u8> for MockDelay {
+        fn delay_ms(&mut self, _ms: u8) {}
+    }
+
+    #[test]
+    fn update_region() {
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+        display
+            .update(&[0xFF; 12], &[0x00; 12], &mut MockDelay {})
+            .unwrap();
+
+        let black = [0x00; 12];
+        let mut red = [0x00; 12];
+        red[4] = 0x01;
+        let region = Region {
+            x: 8,
+            y: 1,
+            cols: 16,
+            rows: 2,
+        };
+        display
+            .update_region(region, &black, &red, &mut MockDelay {})
+            .unwrap();
+
+        for y in 0..4 {
+            for x in 0..24 {
+                let expected = if x == 15 && y == 1 {
+                    Color::Red
+                } else if x >= 8 && (1..3).contains(&y) {
+                    Color::Black
+                } else {
+                    Color::White
+                };
+                assert_eq!(emulator.pixel(x, y), expected, "pixel {}, {}", x, y);
+            }
+        }
+        assert_eq!(emulator.parameters(0x44), &[1, 2]);
+        assert_eq!(emulator.parameters(0x45), &[1, 0, 2, 0]);
+    }
+
+    #[test]
+    fn update_fast() {
+        let lut = [1u8; 70];
+        let fast_lut = [2u8; 70];
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .lut(&lut)
+            .fast_lut(&fast_lut)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+        display.update(&[0xFF], &[0x01], &mut MockDelay {}).unwrap();
+
+        display.update_fast(&[0x0F], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &fast_lut[..]);
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FAST.bits()]
+        );
+        assert_eq!(display.interface().pixel(0, 0), Color::Black);
+        assert_eq!(display.interface().pixel(4, 0), Color::White);
+        assert_eq!(display.interface().pixel(7, 0), Color::Red);
+
+        display.update(&[0xFF], &[0x00], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &lut[..]);
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FULL.bits()]
+        );
+    }
+
+    #[test]
+    fn update_region_unaligned() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        for region in [
+            Region {
+                x: 4,
+                y: 0,
+                cols: 8,
+                rows: 1,
+            },
+            Region {
+                x: 0,
+                y: 0,
+                cols: 4,
+                rows: 1,
+            },
+        ] {
+            assert_eq!(
+                display.update_region(region, &[0; 12], &[0; 12], &mut MockDelay {}),
+                Err(Error::InvalidRegion)
+            );
+        }
+        assert_eq!(display.interface().update_count(), 0);
+    }
+
+    #[test]
+    fn update_region_outside_display() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        for region in [
+            Region {
+                x: 16,
+                y: 0,
+                cols: 16,
+                rows: 1,
+            },
+            Region {
+                x: 0,
+                y: 3,
+                cols: 8,
+                rows: 2,
+            },
+            // The end row overflows
+            Region {
+                x: 0,
+                y: u16::MAX,
+                cols: 8,
+                rows: 2,
+            },
+        ] {
+            assert_eq!(
+                display.update_region(region, &[0; 12], &[0; 12], &mut MockDelay {}),
+                Err(Error::InvalidRegion)
+            );
+        }
+        assert_eq!(display.interface().update_count(), 0);
+    }
+
+    /// Records the commands sent, ignoring their data
+    #[derive(Default)]
+    struct CommandLog {
+        commands: Vec<u8>,
+    }
+
+    impl DisplayInterface for CommandLog {
+        type SpiError = Infallible;
+        type PinError = Infallible;
+
+        fn send_command(&mut self, command: u8) -> Result<(), Error<Infallible, Infallible>> {
+            self.commands.push(command);
+            Ok(())
+        }
+
+        fn send_data(&mut self, _data: &[u8]) -> Result<(), Error<Infallible, Infallible>> {
+            Ok(())
+        }
+
+        fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+        ) -> Result<(), Error<Infallible, Infallible>> {
+            Ok(())
+        }
+
+        fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+            _timeout_ms: u32,
+        ) -> Result<(), Error<Infallible, Infallible>> {
+            Ok(())
+        }
+
+        fn is_busy(&mut self) -> Result<bool, Error<Infallible, Infallible>> {
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn update_region_writes_each_plane_once() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(CommandLog::default(), config);
+        let region = Region {
+            x: 8,
+            y: 0,
+            cols: 8,
+            rows: 4,
+        };
+        display
+            .update_region(region, &[0; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+
+        let commands = &display.interface().commands;
+        assert_eq!(commands.iter().filter(|&&c| c == 0x24).count(), 1);
+        assert_eq!(commands.iter().filter(|&&c| c == 0x26).count(), 1);
+    }
+
+    #[test]
+    fn update_short_buffer() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        assert_eq!(
+            display.update(&[0; 12], &[0; 11], &mut MockDelay {}),
+            Err(Error::InvalidBufferLength)
+        );
+        assert_eq!(
+            display.update_fast(&[0; 11], &mut MockDelay {}),
+            Err(Error::InvalidBufferLength)
+        );
+        assert_eq!(display.interface().update_count(), 0);
+    }
+
+    /// Reports the controller as busy for a number of polls after each update
+    struct SlowEmulator {
+        emulator: Emulator,
+        busy_polls: u32,
+    }
+
+    impl DisplayInterface for SlowEmulator {
+        type SpiError = Infallible;
+        type PinError = Infallible;
+
+        fn send_command(&mut self, command: u8) -> Result<(), Error<Infallible, Infallible>> {
+            assert_eq!(self.busy_polls, 0, "command sent while busy");
+            if command == 0x20 {
+                self.busy_polls = 2;
+            }
+            DisplayInterface::send_command(&mut self.emulator, command)
+        }
+
+        fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Infallible, Infallible>> {
+            DisplayInterface::send_data(&mut self.emulator, data)
+        }
+
+        fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            delay: &mut D,
+        ) -> Result<(), Error<Infallible, Infallible>> {
+            self.busy_polls = 0;
+            DisplayInterface::reset(&mut self.emulator, delay)
+        }
+
+        fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+            _timeout_ms: u32,
+        ) -> Result<(), Error<Infallible, Infallible>> {
+            self.busy_polls = 0;
+            Ok(())
+        }
+
+        fn is_busy(&mut self) -> Result<bool, Error<Infallible, Infallible>> {
+            let busy = self.busy_polls > 0;
+            self.busy_polls = self.busy_polls.saturating_sub(1);
+            Ok(busy)
+        }
+    }
+
+    #[test]
+    fn start_update() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let interface = SlowEmulator {
+            emulator: Emulator::new(),
+            busy_polls: 0,
+        };
+        let mut display = Display::new(interface, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        let mut refresh = display
+            .start_update(&[0; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+        assert!(refresh.is_busy().unwrap());
+        assert!(refresh.is_busy().unwrap());
+        assert!(!refresh.is_busy().unwrap());
+        assert_eq!(display.interface().emulator.update_count(), 1);
+
+        // Commands sent after the handle is dropped wait for the refresh to finish
+        let refresh = display
+            .start_update(&[0; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+        drop(refresh);
+        display.set_temperature(20., &mut MockDelay {}).unwrap();
+        display
+            .update(&[0; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+        assert_eq!(display.interface().emulator.update_count(), 3);
+
+        display
+            .start_update(&[0; 12], &[0; 12], &mut MockDelay {})
+            .unwrap()
+            .finish(&mut MockDelay {})
+            .unwrap();
+        let sleeping = display
+            .deep_sleep(SleepMode::PreserveRAM, &mut MockDelay {})
+            .unwrap();
+        assert!(sleeping.display.interface().emulator.is_sleeping());
+    }
+
+    #[test]
+    fn init_border_waveform() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x3C), &[]);
+
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .border_waveform(BorderWaveform::FollowLut(BorderLut::Lut1))
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x3C), &[0x05]);
+    }
+
+    #[test]
+    fn ram_options() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .ram_options(RamOption::Invert, RamOption::Bypass)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x21), &[0x48]);
+
+        display.update(&[0xF0], &[0x0F], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().pixel(0, 0), Color::Black);
+        assert_eq!(display.interface().pixel(7, 0), Color::White);
+
+        display
+            .set_ram_options(RamOption::Normal, RamOption::Normal, &mut MockDelay {})
+            .unwrap();
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().pixel(0, 0), Color::White);
+        assert_eq!(display.interface().pixel(7, 0), Color::Red);
+
+        // The runtime options replace the configured ones after a reset
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x21), &[0x00]);
+    }
+
+    #[test]
+    fn update_sequence() {
+        use crate::command::DisplayMode;
+
+        // Keep the analog block powered between updates
+        let sequence = UpdateSequence::new()
+            .enable_clock()
+            .enable_analog()
+            .display(DisplayMode::Mode1);
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .update_sequence(sequence)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.update_sequence(), sequence);
+
+        display.update(&[0x00], &[0x00], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x22), &[0xC4]);
+        assert_eq!(display.interface().pixel(0, 0), Color::Black);
+
+        // A sequence without a display step doesn't drive the panel
+        display.set_update_sequence(UpdateSequence::new().disable_analog().disable_clock());
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x22), &[0x03]);
+        assert_eq!(display.interface().update_count(), 1);
+
+        // Fast updates are unaffected
+        display.update_fast(&[0xFF], &mut MockDelay {}).unwrap();
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FAST.bits()]
+        );
+    }
+
+    #[test]
+    fn init_driving_voltages() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .gate_voltage(GateVoltage::from_volts(17.).unwrap())
+            .source_voltages(
+                SourceVoltage::from_volts(11.).unwrap(),
+                SourceVoltage::from_volts(6.).unwrap(),
+                NegativeSourceVoltage::from_volts(-11.).unwrap(),
+            )
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x03), &[0x11]);
+        assert_eq!(display.interface().parameters(0x04), &[0x2D, 0xB2, 0x22]);
+        assert_eq!(display.interface().parameters(0x0C), &[]);
+    }
+
+    #[test]
+    fn deep_sleep_and_wake() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        let sleeping = display
+            .deep_sleep(SleepMode::PreserveRAM, &mut MockDelay {})
+            .unwrap();
+        assert!(sleeping.display.interface().is_sleeping());
+
+        let mut display = sleeping.wake(&mut MockDelay {}).unwrap();
+        assert!(!display.interface().is_sleeping());
+        display
+            .update(&[0xFF; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+        assert_eq!(display.interface().pixel(0, 0), Color::White);
+    }
+
+    /// Fails to set the D/C and RESET pins while `fail` is set, otherwise forwards to the emulator,
+    /// which never fails
+    struct FaultyEmulator {
+        emulator: Emulator,
+        fail: bool,
+    }
+
+    impl DisplayInterface for FaultyEmulator {
+        type SpiError = Infallible;
+        type PinError = ();
+
+        fn send_command(&mut self, command: u8) -> Result<(), Error<Infallible, ()>> {
+            if self.fail {
+                return Err(Error::DcPin(()));
+            }
+            DisplayInterface::send_command(&mut self.emulator, command).unwrap();
+            Ok(())
+        }
+
+        fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Infallible, ()>> {
+            if self.fail {
+                return Err(Error::DcPin(()));
+            }
+            DisplayInterface::send_data(&mut self.emulator, data).unwrap();
+            Ok(())
+        }
+
+        fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            delay: &mut D,
+        ) -> Result<(), Error<Infallible, ()>> {
+            if self.fail {
+                return Err(Error::ResetPin(()));
+            }
+            DisplayInterface::reset(&mut self.emulator, delay).unwrap();
+            Ok(())
+        }
+
+        fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+            _timeout_ms: u32,
+        ) -> Result<(), Error<Infallible, ()>> {
+            Ok(())
+        }
+
+        fn is_busy(&mut self) -> Result<bool, Error<Infallible, ()>> {
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn deep_sleep_and_wake_errors() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let interface = FaultyEmulator {
+            emulator: Emulator::new(),
+            fail: false,
+        };
+        let mut display = Display::new(interface, config);
+        display.reset(&mut MockDelay {}).unwrap();
+        display
+            .update(&[0xFF; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+
+        // The display is handed back when it can't be put to sleep
+        display.interface_mut().fail = true;
+        let SleepError { mut display, error } = display
+            .deep_sleep(SleepMode::PreserveRAM, &mut MockDelay {})
+            .err()
+            .expect("deep sleep succeeded");
+        assert_eq!(error, Error::DcPin(()));
+        assert!(!display.interface().emulator.is_sleeping());
+
+        display.interface_mut().fail = false;
+        let mut sleeping = display
+            .deep_sleep(SleepMode::PreserveRAM, &mut MockDelay {})
+            .unwrap();
+
+        // and the sleeping display when it can't be woken, so waking can be retried
+        sleeping.display.interface_mut().fail = true;
+        let SleepError {
+            display: mut sleeping,
+            error,
+        } = sleeping
+            .wake(&mut MockDelay {})
+            .err()
+            .expect("wake succeeded");
+        assert_eq!(error, Error::ResetPin(()));
+        assert_eq!(sleeping.mode(), SleepMode::PreserveRAM);
+        assert!(sleeping.display.interface().emulator.is_sleeping());
+
+        sleeping.display.interface_mut().fail = false;
+        let mut display = sleeping.wake(&mut MockDelay {}).unwrap();
+        assert!(!display.interface().emulator.is_sleeping());
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().emulator.update_count(), 2);
+    }
+
+    #[test]
+    fn wake_preserves_ram() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert!(!display.ram_valid());
+        assert_eq!(
+            display.refresh_from_ram(&mut MockDelay {}),
+            Err(Error::StaleRam)
+        );
+
+        display
+            .update(&[0xFF; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+        assert!(display.ram_valid());
+
+        // The frame survives sleeping in PreserveRAM, so it can be shown again without the buffers
+        let sleeping = display
+            .deep_sleep(SleepMode::PreserveRAM, &mut MockDelay {})
+            .unwrap();
+        assert_eq!(sleeping.mode(), SleepMode::PreserveRAM);
+        let mut display = sleeping.wake(&mut MockDelay {}).unwrap();
+        assert!(display.ram_valid());
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().update_count(), 2);
+        assert_eq!(display.interface().pixel(0, 0), Color::White);
+
+        // Sleeping in DiscardRAM loses it
+        let sleeping = display
+            .deep_sleep(SleepMode::DiscardRAM, &mut MockDelay {})
+            .unwrap();
+        let mut display = sleeping.wake(&mut MockDelay {}).unwrap();
+        assert!(!display.ram_valid());
+        assert_eq!(
+            display.refresh_from_ram(&mut MockDelay {}),
+            Err(Error::StaleRam)
+        );
+        assert_eq!(display.interface().update_count(), 2);
+
+        // A region update doesn't make a stale RAM valid
+        let region = Region {
+            x: 0,
+            y: 0,
+            cols: 8,
+            rows: 1,
+        };
+        display
+            .update_region(region, &[0xFF; 12], &[0; 12], &mut MockDelay {})
+            .unwrap();
+        assert!(!display.ram_valid());
+    }
+
+    #[test]
+    fn auto_write_commands() {
+        assert_eq!(
+            Pattern::Solid(Color::Red).auto_write_commands(),
+            (
+                Command::AutoWriteBlackPattern(0xE5),
+                Command::AutoWriteRedPattern(0xE5)
+            )
+        );
+        assert_eq!(
+            Pattern::Checkerboard(Step::Px16, Color::Black, Color::White).auto_write_commands(),
+            (
+                Command::AutoWriteBlackPattern(0x11),
+                Command::AutoWriteRedPattern(0x65)
+            )
+        );
+        assert_eq!(
+            Pattern::HorizontalStripes(Step::Px8, Color::White, Color::Red).auto_write_commands(),
+            (
+                Command::AutoWriteBlackPattern(0xE5),
+                Command::AutoWriteRedPattern(0x05)
+            )
+        );
+        assert_eq!(
+            Pattern::VerticalStripes(Step::Px128, Color::Red, Color::Black).auto_write_commands(),
+            (
+                Command::AutoWriteBlackPattern(0xE4),
+                Command::AutoWriteRedPattern(0xE4)
+            )
+        );
+    }
+
+    #[test]
+    fn fill_ram() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 32, cols: 32 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        display.clear_ram(Color::Red, &mut MockDelay {}).unwrap();
+        assert!(display.ram_valid());
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().pixel(0, 0), Color::Red);
+        assert_eq!(display.interface().pixel(31, 31), Color::Red);
+
+        display
+            .fill_ram(
+                Pattern::Checkerboard(Step::Px8, Color::Black, Color::White),
+                &mut MockDelay {},
+            )
+            .unwrap();
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        for (x, y, expected) in [
+            (0, 0, Color::Black),
+            (7, 7, Color::Black),
+            (8, 0, Color::White),
+            (0, 8, Color::White),
+            (8, 8, Color::Black),
+            (31, 16, Color::White),
+            (31, 24, Color::Black),
+        ] {
+            assert_eq!(
+                display.interface().pixel(x, y),
+                expected,
+                "pixel {}, {}",
+                x,
+                y
+            );
+        }
+
+        display
+            .fill_ram(
+                Pattern::VerticalStripes(Step::Px16, Color::White, Color::Red),
+                &mut MockDelay {},
+            )
+            .unwrap();
+        display.refresh_from_ram(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().pixel(15, 20), Color::White);
+        assert_eq!(display.interface().pixel(16, 0), Color::Red);
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::delay::DelayNs for MockDelay {
+        async fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    /// A waker that does nothing, for polling futures by hand.
+    #[cfg(feature = "async")]
+    fn noop_waker() -> core::task::Waker {
+        use core::task::{RawWaker, RawWakerVTable, Waker};
+
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+        // The vtable functions ignore the data pointer, so any pointer is valid
+        unsafe { Waker::from_raw(RAW) }
+    }
+
+    /// Run a future that is expected to complete without waiting, as the emulator's do.
+    #[cfg(feature = "async")]
+    fn block_on<F: core::future::Future>(future: F) -> F::Output {
+        use core::task::{Context, Poll};
+
+        let mut future = core::pin::pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(&noop_waker()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future did not complete"),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn update_async() {
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(Emulator::new(), config);
+        block_on(display.reset_async(&mut MockDelay {})).unwrap();
+
+        let mut black = [0xFF; 12];
+        let mut red = [0x00; 12];
+        black[0] = 0x7F;
+        red[11] = 0x01;
+        block_on(display.update_async(&black, &red, &mut MockDelay {})).unwrap();
+        assert_eq!(display.interface().update_count(), 1);
+        assert_eq!(display.interface().pixel(0, 0), Color::Black);
+        assert_eq!(display.interface().pixel(1, 0), Color::White);
+        assert_eq!(display.interface().pixel(23, 3), Color::Red);
+
+        black[5] = 0x00;
+        let region = Region {
+            x: 16,
+            y: 1,
+            cols: 8,
+            rows: 1,
+        };
+        block_on(display.update_region_async(region, &black, &red, &mut MockDelay {})).unwrap();
+        assert_eq!(display.interface().pixel(16, 1), Color::Black);
+        assert_eq!(display.interface().pixel(0, 0), Color::Black);
+    }
+
+    /// Completes after being polled a second time
+    #[cfg(feature = "async")]
+    struct YieldOnce(bool);
+
+    #[cfg(feature = "async")]
+    impl core::future::Future for YieldOnce {
+        type Output = ();
+
+        fn poll(
+            mut self: core::pin::Pin<&mut Self>,
+            _cx: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<()> {
+            if self.0 {
+                core::task::Poll::Ready(())
+            } else {
+                self.0 = true;
+                core::task::Poll::Pending
+            }
+        }
+    }
+
+    /// Stays busy after each update until `busy_wait` has been polled twice
+    #[cfg(feature = "async")]
+    struct AsyncSlowEmulator {
+        emulator: Emulator,
+        busy: bool,
+        pending: bool,
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncDisplayInterface for AsyncSlowEmulator {
+        type SpiError = Infallible;
+        type PinError = Infallible;
+
+        async fn send_command(&mut self, command: u8) -> Result<(), Error<Infallible, Infallible>> {
+            assert!(!self.busy, "command sent while busy");
+            if command == 0x20 {
+                self.busy = true;
+                self.pending = true;
+            }
+            DisplayInterface::send_command(&mut self.emulator, command)
+        }
+
+        async fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Infallible, Infallible>> {
+            DisplayInterface::send_data(&mut self.emulator, data)
+        }
+
+        async fn reset<D: DelayNs>(
+            &mut self,
+            delay: &mut D,
+        ) -> Result<(), Error<Infallible, Infallible>> {
+            self.busy = false;
+            AsyncDisplayInterface::reset(&mut self.emulator, delay).await
+        }
+
+        async fn busy_wait(&mut self) -> Result<(), Error<Infallible, Infallible>> {
+            if core::mem::replace(&mut self.pending, false) {
+                YieldOnce(false).await;
+            }
+            self.busy = false;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn cancelled_update_async() {
+        use core::future::Future;
+        use core::task::Context;
+
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 4, cols: 24 })
+            .build()
+            .expect("invalid config");
+        let interface = AsyncSlowEmulator {
+            emulator: Emulator::new(),
+            busy: false,
+            pending: false,
+        };
+        let mut display = Display::new(interface, config);
+        block_on(display.reset_async(&mut MockDelay {})).unwrap();
+
+        // Drop the update while it's waiting for the refresh, as a timeout would
+        {
+            let mut delay = MockDelay {};
+            let update = display.update_async(&[0; 12], &[0; 12], &mut delay);
+            let mut update = core::pin::pin!(update);
+            let poll = update
+                .as_mut()
+                .poll(&mut Context::from_waker(&noop_waker()));
+            assert!(poll.is_pending());
+        }
+        assert!(display.interface().busy);
+
+        // The next call waits for the refresh to finish before sending commands
+        block_on(display.set_temperature_async(20.)).unwrap();
+        assert!(!display.interface().busy);
+        assert_eq!(display.interface().emulator.update_count(), 1);
+    }
+
+    #[test]
+    fn temperature_to_register_conversion() {
+        assert_eq!(temperature_to_register(0.), 0x000);
+        assert_eq!(temperature_to_register(25.), 0x190);
+        assert_eq!(temperature_to_register(-0.0625), 0xFFF);
+        assert_eq!(temperature_to_register(-25.), 0xE70);
+        assert_eq!(temperature_to_register(200.), 0x7FF);
+        assert_eq!(temperature_to_register(-200.), 0x800);
+    }
+
+    #[test]
+    fn temperature_register_conversion() {
+        assert_eq!(temperature_from_register(0x000), 0.);
+        assert_eq!(temperature_from_register(0x190), 25.);
+        assert_eq!(temperature_from_register(0x7FF), 127.9375);
+        assert_eq!(temperature_from_register(0xFFF), -0.0625);
+        assert_eq!(temperature_from_register(0xE70), -25.);
+    }
+
+    #[test]
+    fn sense_vcom() {
+        let mut emulator = Emulator::new();
+        emulator.set_sensed_vcom(Vcom::from_volts(-1.2).unwrap());
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 2, cols: 8 })
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        // Without applying the configured VCOM is restored
+        let vcom = display
+            .sense_vcom(
+                VcomSenseDuration::from_secs(10).unwrap(),
+                false,
+                &mut MockDelay {},
+            )
+            .unwrap();
+        assert_eq!(vcom.register(), 0x30);
+        assert_eq!(display.interface().parameters(0x29), &[9]);
+        assert_eq!(display.interface().parameters(0x2C), &[0x3C]);
+
+        // Once applied it's kept across a reset
+        display
+            .sense_vcom(
+                VcomSenseDuration::from_secs(10).unwrap(),
+                true,
+                &mut MockDelay {},
+            )
+            .unwrap();
+        assert_eq!(display.interface().parameters(0x2C), &[0x30]);
+        display.reset(&mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().parameters(0x2C), &[0x30]);
+        assert_eq!(display.interface().update_count(), 0);
+    }
+
+    #[test]
+    fn fast_update_restores_otp_lut() {
+        let fast_lut = [3u8; 70];
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .fast_lut(&fast_lut)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FULL.bits()]
+        );
+
+        display.update_fast(&[0xFF], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &fast_lut[..]);
+
+        // Without a LUT in the config the controller's own is reloaded from OTP
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert!(display.interface().lut().is_empty());
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FULL.load_lut().bits()]
+        );
+
+        // and then left in place
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FULL.bits()]
+        );
+    }
+
+    #[test]
+    fn update_sequence_loading_lut() {
+        let lut = [1u8; 70];
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .lut(&lut)
+            .update_sequence(UpdateSequence::FULL.load_lut())
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert!(display.interface().lut().is_empty());
+
+        // The LUT from the config is written again once the sequence stops replacing it
+        display.set_update_sequence(UpdateSequence::FULL);
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &lut[..]);
+    }
+
+    #[test]
+    fn temperature() {
+        let mut emulator = Emulator::new();
+        emulator.set_sensor_temperature(-12.5);
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 2, cols: 8 })
+            .temperature_sensor(TemperatureSensor::Internal)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        assert_eq!(display.temperature(&mut MockDelay {}).unwrap(), -12.5);
+        assert_eq!(emulator.parameters(0x18), &[0x80]);
+        assert_eq!(emulator.update_count(), 0);
+    }
+
+    #[test]
+    fn temperature_luts() {
+        let default_lut = [0u8; 70];
+        let cold_lut = [1u8; 70];
+        let warm_lut = [2u8; 70];
+        let luts = [
+            TemperatureLut {
+                temperatures: -20..10,
+                lut: &cold_lut,
+            },
+            TemperatureLut {
+                temperatures: 10..40,
+                lut: &warm_lut,
+            },
+        ];
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .lut(&default_lut)
+            .temperature_luts(&luts)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &default_lut[..]);
+
+        display.set_temperature(-5.5, &mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &cold_lut[..]);
+        assert_eq!(display.interface().temperature_register(), 0xFA8);
+
+        display.interface_mut().set_sensor_temperature(21.);
+        display.temperature(&mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &warm_lut[..]);
+
+        display.set_temperature(45., &mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &default_lut[..]);
+    }
+
+    #[test]
+    fn temperature_luts_without_default() {
+        let cold_lut = [1u8; 70];
+        let luts = [TemperatureLut {
+            temperatures: -20..10,
+            lut: &cold_lut,
+        }];
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .temperature_luts(&luts)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        display.set_temperature(0., &mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &cold_lut[..]);
+
+        // Out of range the controller's own LUT is reloaded rather than keeping the cold one
+        display.set_temperature(25., &mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert!(display.interface().lut().is_empty());
+        assert_eq!(
+            display.interface().parameters(0x22),
+            &[UpdateSequence::FULL.load_lut().bits()]
+        );
+    }
+
+    #[test]
+    fn temperature_luts_follow_sensor_reads() {
+        let default_lut = [0u8; 70];
+        let cold_lut = [1u8; 70];
+        let luts = [TemperatureLut {
+            temperatures: -20..10,
+            lut: &cold_lut,
+        }];
+        let mut emulator = Emulator::new();
+        emulator.set_sensor_temperature(0.);
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 1, cols: 8 })
+            .lut(&default_lut)
+            .temperature_luts(&luts)
+            .build()
+            .expect("invalid config");
+        let mut display = Display::new(&mut emulator, config);
+        display.reset(&mut MockDelay {}).unwrap();
+
+        // The sensor isn't read by an update
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &default_lut[..]);
+
+        display.temperature(&mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &cold_lut[..]);
+
+        // so the last temperature read is used until it's read again
+        display.interface_mut().set_sensor_temperature(25.);
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &cold_lut[..]);
+
+        display.temperature(&mut MockDelay {}).unwrap();
+        display.update(&[0], &[0], &mut MockDelay {}).unwrap();
+        assert_eq!(display.interface().lut(), &default_lut[..]);
     }
 }
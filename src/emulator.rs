@@ -0,0 +1,954 @@
+//! A headless emulation of the SSD1675 controller.
+//!
+//! [Emulator] implements [DisplayInterface] so it can be used in place of a real [Interface]. It
+//! decodes the command and data bytes produced by [Command] and [BufCommand], maintains the
+//! black/white and red RAM the way the controller would, and latches the RAM contents into a
+//! panel image when a display update is activated. This allows code driving a display to be
+//! tested without any hardware attached.
+//!
+//! ### Example
+//!
+//! ```
+//! # extern crate embedded_hal;
+//! # extern crate ssd1675;
+//! use ssd1675::emulator::Emulator;
+//! use ssd1675::{Builder, Color, Dimensions, Display};
+//!
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u8> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u8) {}
+//! # }
+//! # let mut delay = Delay;
+//! let mut emulator = Emulator::new();
+//! let config = Builder::new()
+//!     .dimensions(Dimensions { rows: 2, cols: 8 })
+//!     .build()
+//!     .expect("invalid configuration");
+//! let mut display = Display::new(&mut emulator, config);
+//! display.reset(&mut delay).unwrap();
+//! display.update(&[0xF0, 0xFF], &[0x00, 0x01], &mut delay).unwrap();
+//!
+//! assert_eq!(emulator.pixel(0, 0), Color::White);
+//! assert_eq!(emulator.pixel(4, 0), Color::Black);
+//! assert_eq!(emulator.pixel(7, 1), Color::Red);
+//! ```
+//!
+//! [Emulator]: struct.Emulator.html
+//! [DisplayInterface]: ../interface/trait.DisplayInterface.html
+//! [Interface]: ../interface/struct.Interface.html
+//! [Command]: ../command/enum.Command.html
+//! [BufCommand]: ../command/enum.BufCommand.html
+
+#[cfg(feature = "async")]
+use crate::asynch::AsyncDisplayInterface;
+use crate::color::Color;
+use crate::config::Vcom;
+use crate::display::{Dimensions, Rotation, MAX_GATE_OUTPUTS, MAX_SOURCE_OUTPUTS};
+use crate::error::Error;
+use crate::graphics;
+use crate::hal;
+use crate::interface::{DisplayInterface, ReadInterface};
+use core::convert::Infallible;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(feature = "std")]
+extern crate png;
+
+/// The number of bytes in one row of controller RAM
+pub const RAM_COLS: usize = MAX_SOURCE_OUTPUTS as usize / 8;
+/// The number of rows of controller RAM
+pub const RAM_ROWS: usize = MAX_GATE_OUTPUTS as usize;
+const RAM_SIZE: usize = RAM_COLS * RAM_ROWS;
+
+// Enough to hold the parameters of any command except the LUT and RAM writes
+const MAX_PARAMS: usize = 10;
+const LUT_SIZE: usize = 70;
+
+// Power on reset value of the data entry mode register (IncrementYIncrementX, Horizontal)
+const POR_DATA_ENTRY_MODE: u8 = 0b011;
+// Power on reset value of the display update control 2 register
+const POR_UPDATE_SEQUENCE: u8 = 0xFF;
+// Reported in the chip ID bits of the status register
+const CHIP_ID: u8 = 0b01;
+// Bits of the display update control 2 register
+const LOAD_TEMPERATURE: u8 = 0x20;
+const LOAD_LUT: u8 = 0x10;
+const DISPLAY_MODE_1: u8 = 0x04;
+const DISPLAY_MODE_2: u8 = 0x08;
+
+#[derive(Clone, Copy)]
+enum Plane {
+    Black,
+    Red,
+}
+
+/// An emulated SSD1675 controller.
+///
+/// The emulator holds the full 160x296 RAM of the controller, regardless of the dimensions of
+/// the display being emulated. The RAM is addressed the same way as the controller: X addresses
+/// are in units of bytes (8 pixels), Y addresses are in rows.
+pub struct Emulator {
+    black_ram: [u8; RAM_SIZE],
+    red_ram: [u8; RAM_SIZE],
+    black_panel: [u8; RAM_SIZE],
+    red_panel: [u8; RAM_SIZE],
+    lut: [u8; LUT_SIZE],
+    lut_len: usize,
+    params: [[u8; MAX_PARAMS]; 256],
+    param_lens: [u8; 256],
+    command: Option<u8>,
+    data_index: usize,
+    x_start: u8,
+    x_end: u8,
+    y_start: u16,
+    y_end: u16,
+    x_address: u8,
+    y_address: u16,
+    data_entry_mode: u8,
+    read_red_ram: bool,
+    ram_options: u8,
+    update_sequence: u8,
+    temperature: u16,
+    sensor_temperature: u16,
+    sleeping: bool,
+    update_count: usize,
+    sensed_vcom: u8,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        let mut emulator = Emulator {
+            black_ram: [0; RAM_SIZE],
+            red_ram: [0; RAM_SIZE],
+            black_panel: [0xFF; RAM_SIZE],
+            red_panel: [0; RAM_SIZE],
+            lut: [0; LUT_SIZE],
+            lut_len: 0,
+            params: [[0; MAX_PARAMS]; 256],
+            param_lens: [0; 256],
+            command: None,
+            data_index: 0,
+            x_start: 0,
+            x_end: 0,
+            y_start: 0,
+            y_end: 0,
+            x_address: 0,
+            y_address: 0,
+            data_entry_mode: POR_DATA_ENTRY_MODE,
+            read_red_ram: false,
+            ram_options: 0,
+            update_sequence: POR_UPDATE_SEQUENCE,
+            temperature: 0,
+            sensor_temperature: 0,
+            sleeping: false,
+            update_count: 0,
+            sensed_vcom: 0,
+        };
+        emulator.soft_reset();
+        emulator
+    }
+}
+
+impl Emulator {
+    /// Create a new emulator in the power on reset state.
+    ///
+    /// The panel starts out white.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the colour of the pixel at `x`, `y` as it was shown by the most recent display
+    /// update.
+    ///
+    /// Coordinates are in the native orientation of the controller. Pixels outside the controller
+    /// RAM are reported as white.
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        if x >= MAX_SOURCE_OUTPUTS as u32 || y >= MAX_GATE_OUTPUTS as u32 {
+            return Color::White;
+        }
+
+        let index = y as usize * RAM_COLS + x as usize / 8;
+        let bit = 0x80 >> (x % 8);
+        if self.red_panel[index] & bit != 0 {
+            Color::Red
+        } else if self.black_panel[index] & bit != 0 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// The current contents of the black/white RAM, `RAM_COLS` bytes per row.
+    pub fn black_ram(&self) -> &[u8] {
+        &self.black_ram
+    }
+
+    /// The current contents of the red RAM, `RAM_COLS` bytes per row.
+    pub fn red_ram(&self) -> &[u8] {
+        &self.red_ram
+    }
+
+    /// The contents of the LUT register as last written with `WriteLUT`.
+    ///
+    /// The contents of OTP are not emulated, so this is empty once an update sequence has loaded
+    /// the LUT from OTP.
+    pub fn lut(&self) -> &[u8] {
+        &self.lut[..self.lut_len]
+    }
+
+    /// The parameters most recently sent with `command`.
+    ///
+    /// Only the first 10 parameters are retained. RAM and LUT writes are not recorded here, use
+    /// `black_ram`, `red_ram` and `lut` instead.
+    pub fn parameters(&self, command: u8) -> &[u8] {
+        let len = self.param_lens[command as usize] as usize;
+        &self.params[command as usize][..len]
+    }
+
+    /// The number of display updates that have driven the panel.
+    pub fn update_count(&self) -> usize {
+        self.update_count
+    }
+
+    /// Returns true if the controller is in deep sleep.
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Returns the RAM X (byte) and Y (row) address counters.
+    pub fn address(&self) -> (u8, u16) {
+        (self.x_address, self.y_address)
+    }
+
+    /// Returns a view of the panel as it appears on a display with the supplied dimensions and
+    /// rotation.
+    ///
+    /// These should be the same values that were used to build the `Config` of the display.
+    pub fn frame(&self, dimensions: &Dimensions, rotation: Rotation) -> Frame<'_> {
+        Frame {
+            emulator: self,
+            rows: dimensions.rows,
+            cols: dimensions.cols,
+            rotation,
+        }
+    }
+
+    /// Set the temperature that the internal and external sensors will report, in degrees
+    /// Celsius.
+    ///
+    /// The temperature register is only updated from the sensor when a display update sequence
+    /// that loads the temperature is activated.
+    pub fn set_sensor_temperature(&mut self, celsius: f32) {
+        self.sensor_temperature = ((celsius * 16.) as i16 as u16) & 0xFFF;
+    }
+
+    /// Set the VCOM that VCOM sensing will measure.
+    pub fn set_sensed_vcom(&mut self, vcom: Vcom) {
+        self.sensed_vcom = vcom.register();
+    }
+
+    /// The value of the 12-bit temperature register.
+    pub fn temperature_register(&self) -> u16 {
+        self.temperature
+    }
+
+    fn soft_reset(&mut self) {
+        self.x_start = 0;
+        self.x_end = (RAM_COLS - 1) as u8;
+        self.y_start = 0;
+        self.y_end = (RAM_ROWS - 1) as u16;
+        self.x_address = 0;
+        self.y_address = 0;
+        self.data_entry_mode = POR_DATA_ENTRY_MODE;
+        self.read_red_ram = false;
+        self.ram_options = 0;
+        self.update_sequence = POR_UPDATE_SEQUENCE;
+    }
+
+    fn hardware_reset(&mut self) {
+        self.command = None;
+        self.data_index = 0;
+        self.sleeping = false;
+        self.soft_reset();
+    }
+
+    fn command(&mut self, command: u8) {
+        self.command = Some(command);
+        self.data_index = 0;
+
+        match command {
+            0x12 => self.soft_reset(),
+            0x28 => self.sense_vcom(),
+            0x20 => self.update_display(),
+            0x32 => self.lut_len = 0,
+            _ => self.param_lens[command as usize] = 0,
+        }
+    }
+
+    fn data(&mut self, byte: u8) {
+        let command = match self.command {
+            Some(command) => command,
+            None => return,
+        };
+        let index = self.data_index;
+        self.data_index += 1;
+
+        match command {
+            0x24 => self.write_ram(Plane::Black, byte),
+            0x26 => self.write_ram(Plane::Red, byte),
+            0x32 => {
+                if index < LUT_SIZE {
+                    self.lut[index] = byte;
+                    self.lut_len = index + 1;
+                }
+            }
+            _ => {
+                if index < MAX_PARAMS {
+                    self.params[command as usize][index] = byte;
+                    self.param_lens[command as usize] = index as u8 + 1;
+                    self.parameter(command, index);
+                }
+            }
+        }
+    }
+
+    fn parameter(&mut self, command: u8, index: usize) {
+        let params = self.params[command as usize];
+        match (command, index) {
+            (0x10, 0) => {
+                let mode = params[0] & 0b11;
+                if mode != 0 {
+                    self.sleeping = true;
+                }
+                if mode == 0b11 {
+                    // RAM contents are not retained in deep sleep mode 2
+                    self.black_ram = [0; RAM_SIZE];
+                    self.red_ram = [0; RAM_SIZE];
+                }
+            }
+            (0x11, 0) => self.data_entry_mode = params[0] & 0b111,
+            (0x1A, 1) => self.temperature = u16::from_be_bytes([params[0], params[1]]) >> 4,
+            (0x21, 0) => self.ram_options = params[0],
+            (0x22, 0) => self.update_sequence = params[0],
+            (0x41, 0) => self.read_red_ram = params[0] & 0b1 != 0,
+            (0x44, 0) => self.x_start = params[0] & 0x1F,
+            (0x44, 1) => self.x_end = params[1] & 0x1F,
+            (0x45, 1) => self.y_start = u16::from_le_bytes([params[0], params[1] & 0x01]),
+            (0x45, 3) => self.y_end = u16::from_le_bytes([params[2], params[3] & 0x01]),
+            (0x46, 0) => self.auto_write(Plane::Red, params[0]),
+            (0x47, 0) => self.auto_write(Plane::Black, params[0]),
+            (0x4E, 0) => self.x_address = params[0] & 0x1F,
+            (0x4F, 0) => self.y_address = u16::from(params[0]),
+            (0x4F, 1) => self.y_address = u16::from_le_bytes([params[0], params[1] & 0x01]),
+            _ => {}
+        }
+    }
+
+    fn read(&mut self) -> u8 {
+        let command = match self.command {
+            Some(command) => command,
+            None => return 0,
+        };
+        let index = self.data_index;
+        self.data_index += 1;
+
+        match command {
+            0x1B => (self.temperature << 4).to_be_bytes()[index.min(1)],
+            // The first byte read from RAM is a dummy byte
+            0x27 if index == 0 => 0,
+            0x27 => {
+                let plane = if self.read_red_ram {
+                    Plane::Red
+                } else {
+                    Plane::Black
+                };
+                self.read_ram(plane)
+            }
+            // Only the VCOM register of the display option is emulated
+            0x2D if index == 1 => self.parameters(0x2C).first().cloned().unwrap_or(0),
+            0x2E => self.parameters(0x38).get(index).cloned().unwrap_or(0),
+            0x2F => CHIP_ID,
+            _ => 0,
+        }
+    }
+
+    fn read_ram(&mut self, plane: Plane) -> u8 {
+        let x = self.x_address as usize;
+        let y = self.y_address as usize;
+        let byte = if x < RAM_COLS && y < RAM_ROWS {
+            match plane {
+                Plane::Black => self.black_ram[y * RAM_COLS + x],
+                Plane::Red => self.red_ram[y * RAM_COLS + x],
+            }
+        } else {
+            0
+        };
+
+        self.advance_address();
+        byte
+    }
+
+    fn write_ram(&mut self, plane: Plane, byte: u8) {
+        let x = self.x_address as usize;
+        let y = self.y_address as usize;
+        if x < RAM_COLS && y < RAM_ROWS {
+            let ram = match plane {
+                Plane::Black => &mut self.black_ram,
+                Plane::Red => &mut self.red_ram,
+            };
+            ram[y * RAM_COLS + x] = byte;
+        }
+
+        self.advance_address();
+    }
+
+    /// Fill the whole of a RAM plane with the regular pattern described by an auto write RAM
+    /// parameter.
+    fn auto_write(&mut self, plane: Plane, pattern: u8) {
+        let height = match (pattern >> 4) & 0b111 {
+            step @ 0..=5 => 8 << step,
+            _ => RAM_ROWS,
+        };
+        let width = match pattern & 0b111 {
+            step @ 0..=4 => 8 << step,
+            _ => MAX_SOURCE_OUTPUTS as usize,
+        };
+        let first = pattern & 0x80 != 0;
+
+        let ram = match plane {
+            Plane::Black => &mut self.black_ram,
+            Plane::Red => &mut self.red_ram,
+        };
+        for (index, byte) in ram.iter_mut().enumerate() {
+            let y = index / RAM_COLS;
+            let x = (index % RAM_COLS) * 8;
+            // Steps are at least 8 pixels wide so each byte lies within a single step
+            let odd = (x / width + y / height) % 2 == 1;
+            *byte = if first != odd { 0xFF } else { 0x00 };
+        }
+    }
+
+    /// Move the address counters on to the next position according to the data entry mode,
+    /// wrapping within the RAM window.
+    fn advance_address(&mut self) {
+        let increment_x = self.data_entry_mode & 0b001 != 0;
+        let increment_y = self.data_entry_mode & 0b010 != 0;
+        let vertical = self.data_entry_mode & 0b100 != 0;
+
+        if vertical {
+            if self.step_y(increment_y) {
+                self.step_x(increment_x);
+            }
+        } else if self.step_x(increment_x) {
+            self.step_y(increment_y);
+        }
+    }
+
+    /// Step the X address counter, returns true if it wrapped.
+    fn step_x(&mut self, increment: bool) -> bool {
+        if increment {
+            if self.x_address >= self.x_end {
+                self.x_address = self.x_start;
+                return true;
+            }
+            self.x_address += 1;
+        } else {
+            if self.x_address <= self.x_start {
+                self.x_address = self.x_end;
+                return true;
+            }
+            self.x_address -= 1;
+        }
+        false
+    }
+
+    /// Step the Y address counter, returns true if it wrapped.
+    fn step_y(&mut self, increment: bool) -> bool {
+        if increment {
+            if self.y_address >= self.y_end {
+                self.y_address = self.y_start;
+                return true;
+            }
+            self.y_address += 1;
+        } else {
+            if self.y_address <= self.y_start {
+                self.y_address = self.y_end;
+                return true;
+            }
+            self.y_address -= 1;
+        }
+        false
+    }
+
+    /// Load the sensed VCOM into the VCOM register.
+    fn sense_vcom(&mut self) {
+        self.params[0x2C][0] = self.sensed_vcom;
+        self.param_lens[0x2C] = 1;
+    }
+
+    fn update_display(&mut self) {
+        if self.update_sequence & LOAD_TEMPERATURE != 0 {
+            self.temperature = self.sensor_temperature;
+        }
+        if self.update_sequence & LOAD_LUT != 0 {
+            self.lut_len = 0;
+        }
+
+        if self.update_sequence & (DISPLAY_MODE_1 | DISPLAY_MODE_2) == 0 {
+            return;
+        }
+
+        let black_option = self.ram_options & 0x0F;
+        let red_option = self.ram_options >> 4;
+        for i in 0..RAM_SIZE {
+            self.black_panel[i] = apply_ram_option(black_option, self.black_ram[i]);
+            self.red_panel[i] = apply_ram_option(red_option, self.red_ram[i]);
+        }
+        self.update_count += 1;
+    }
+}
+
+/// The visible image of an emulated panel.
+///
+/// Coordinates are in the rotated orientation of the display, the same as those used when
+/// drawing on a `GraphicDisplay`. With the `std` feature enabled the image can be exported as PPM
+/// or PNG.
+pub struct Frame<'e> {
+    emulator: &'e Emulator,
+    rows: u16,
+    cols: u8,
+    rotation: Rotation,
+}
+
+impl<'e> Frame<'e> {
+    /// The width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        match self.rotation {
+            Rotation::Rotate0 | Rotation::Rotate180 => self.cols.into(),
+            Rotation::Rotate90 | Rotation::Rotate270 => self.rows.into(),
+        }
+    }
+
+    /// The height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        match self.rotation {
+            Rotation::Rotate0 | Rotation::Rotate180 => self.rows.into(),
+            Rotation::Rotate90 | Rotation::Rotate270 => self.cols.into(),
+        }
+    }
+
+    /// Returns the colour of the pixel at `x`, `y`.
+    ///
+    /// Pixels outside the image are reported as white.
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        if x >= self.width() || y >= self.height() {
+            return Color::White;
+        }
+
+        let (index, bit) =
+            graphics::rotation(x, y, self.cols.into(), self.rows.into(), self.rotation);
+        let bytes_per_row = u32::from(self.cols) / 8;
+        let native_x = (index % bytes_per_row) * 8 + bit.leading_zeros();
+        let native_y = index / bytes_per_row;
+        self.emulator.pixel(native_x, native_y)
+    }
+
+    /// Write the image as a binary PPM (P6).
+    #[cfg(feature = "std")]
+    pub fn write_ppm<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        writer.write_all(&self.rgb())
+    }
+
+    /// Write the image as an 8-bit RGB PNG.
+    #[cfg(feature = "std")]
+    pub fn write_png<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width(), self.height());
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgb())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn rgb(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.width() as usize * self.height() as usize * 3);
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let rgb = match self.pixel(x, y) {
+                    Color::Black => [0x00, 0x00, 0x00],
+                    Color::White => [0xFF, 0xFF, 0xFF],
+                    Color::Red => [0xFF, 0x00, 0x00],
+                };
+                data.extend_from_slice(&rgb);
+            }
+        }
+        data
+    }
+}
+
+fn apply_ram_option(option: u8, byte: u8) -> u8 {
+    match option {
+        0b0100 => 0x00,  // Bypass RAM content as 0
+        0b1000 => !byte, // Inverse RAM content
+        _ => byte,
+    }
+}
+
+impl DisplayInterface for Emulator {
+    type SpiError = Infallible;
+    type PinError = Infallible;
+
+    fn send_command(&mut self, command: u8) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        // A controller in deep sleep ignores everything until it is reset
+        if !self.sleeping {
+            self.command(command);
+        }
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        if !self.sleeping {
+            for byte in data {
+                self.data(*byte);
+            }
+        }
+        Ok(())
+    }
+
+    fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        _delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.hardware_reset();
+        Ok(())
+    }
+
+    fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        _delay: &mut D,
+        _timeout_ms: u32,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        // The emulator completes every operation immediately
+        Ok(())
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+        Ok(false)
+    }
+}
+
+impl ReadInterface for Emulator {
+    fn read_data(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        for byte in buffer.iter_mut() {
+            *byte = if self.sleeping { 0 } else { self.read() };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncDisplayInterface for Emulator {
+    type SpiError = Infallible;
+    type PinError = Infallible;
+
+    async fn send_command(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        DisplayInterface::send_command(self, command)
+    }
+
+    async fn send_data(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        DisplayInterface::send_data(self, data)
+    }
+
+    async fn reset<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        _delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.hardware_reset();
+        Ok(())
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        // The emulator completes every operation immediately
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{BufCommand, Command, DataEntryMode, DeepSleepMode, IncrementAxis, Ram};
+    use crate::{Builder, Dimensions, Display};
+
+    struct MockDelay {}
+
+    impl hal::blocking::delay::DelayMs<u8> for MockDelay {
+        fn delay_ms(&mut self, _ms: u8) {}
+    }
+
+    #[test]
+    fn update_latches_ram_into_panel() {
+        let mut emulator = Emulator::new();
+        let config = Builder::new()
+            .dimensions(Dimensions { rows: 3, cols: 16 })
+            .build()
+            .expect("invalid config");
+
+        {
+            let mut display = Display::new(&mut emulator, config);
+            display.reset(&mut MockDelay {}).unwrap();
+            display
+                .update(
+                    &[0xFF, 0x00, 0x0F, 0xF0, 0xFF, 0xFF],
+                    &[0x00, 0x00, 0x00, 0x00, 0x80, 0x01],
+                    &mut MockDelay {},
+                )
+                .unwrap();
+        }
+
+        assert_eq!(emulator.update_count(), 1);
+        assert_eq!(emulator.pixel(0, 0), Color::White);
+        assert_eq!(emulator.pixel(8, 0), Color::Black);
+        assert_eq!(emulator.pixel(3, 1), Color::Black);
+        assert_eq!(emulator.pixel(4, 1), Color::White);
+        assert_eq!(emulator.pixel(12, 1), Color::Black);
+        assert_eq!(emulator.pixel(0, 2), Color::Red);
+        assert_eq!(emulator.pixel(1, 2), Color::White);
+        assert_eq!(emulator.pixel(15, 2), Color::Red);
+    }
+
+    #[test]
+    fn ram_is_not_shown_until_update() {
+        let mut emulator = Emulator::new();
+        BufCommand::WriteBlackData(&[0x00])
+            .execute(&mut emulator)
+            .unwrap();
+
+        assert_eq!(emulator.black_ram()[0], 0x00);
+        assert_eq!(emulator.pixel(0, 0), Color::White);
+
+        Command::UpdateDisplay.execute(&mut emulator).unwrap();
+        assert_eq!(emulator.pixel(0, 0), Color::Black);
+    }
+
+    #[test]
+    fn address_wraps_within_window() {
+        let mut emulator = Emulator::new();
+        Command::StartEndXPosition(1, 2)
+            .execute(&mut emulator)
+            .unwrap();
+        Command::StartEndYPosition(4, 5)
+            .execute(&mut emulator)
+            .unwrap();
+        Command::XAddress(1).execute(&mut emulator).unwrap();
+        Command::YAddress(4).execute(&mut emulator).unwrap();
+        BufCommand::WriteBlackData(&[0x01, 0x02, 0x03, 0x04, 0x05])
+            .execute(&mut emulator)
+            .unwrap();
+
+        let ram = emulator.black_ram();
+        // The last byte wrapped back to the start of the window
+        assert_eq!(&ram[4 * RAM_COLS..4 * RAM_COLS + 4], &[0, 0x05, 0x02, 0]);
+        assert_eq!(&ram[5 * RAM_COLS..5 * RAM_COLS + 4], &[0, 0x03, 0x04, 0]);
+        assert_eq!(emulator.red_ram()[4 * RAM_COLS + 1], 0x00);
+        assert_eq!(emulator.address(), (2, 4));
+    }
+
+    #[test]
+    fn vertical_decrementing_entry_mode() {
+        let mut emulator = Emulator::new();
+        Command::DataEntryMode(DataEntryMode::DecrementXDecrementY, IncrementAxis::Vertical)
+            .execute(&mut emulator)
+            .unwrap();
+        Command::XAddress(1).execute(&mut emulator).unwrap();
+        Command::YAddress(1).execute(&mut emulator).unwrap();
+        BufCommand::WriteRedData(&[0xAA, 0xBB, 0xCC])
+            .execute(&mut emulator)
+            .unwrap();
+
+        let ram = emulator.red_ram();
+        assert_eq!(ram[RAM_COLS + 1], 0xAA);
+        assert_eq!(ram[1], 0xBB);
+        assert_eq!(ram[(RAM_ROWS - 1) * RAM_COLS], 0xCC);
+    }
+
+    #[test]
+    fn auto_write_pattern() {
+        let mut emulator = Emulator::new();
+        // Red RAM: 16 column wide stripes starting with 1
+        Command::AutoWriteRedPattern(0xE1)
+            .execute(&mut emulator)
+            .unwrap();
+        // B/W RAM: 8 row high, 32 column wide steps starting with 0
+        Command::AutoWriteBlackPattern(0x02)
+            .execute(&mut emulator)
+            .unwrap();
+
+        let red = emulator.red_ram();
+        assert_eq!(&red[..5], &[0xFF, 0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(red[(RAM_ROWS - 1) * RAM_COLS + 19], 0x00);
+
+        let black = emulator.black_ram();
+        assert_eq!(&black[..5], &[0x00, 0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(
+            &black[8 * RAM_COLS..8 * RAM_COLS + 5],
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0x00]
+        );
+        assert_eq!(emulator.parameters(0x47), &[0x02]);
+    }
+
+    #[test]
+    fn frame_honours_rotation() {
+        let mut emulator = Emulator::new();
+        let dimensions = Dimensions { rows: 2, cols: 8 };
+        {
+            let config = Builder::new()
+                .dimensions(Dimensions { rows: 2, cols: 8 })
+                .build()
+                .expect("invalid config");
+            let mut display = Display::new(&mut emulator, config);
+            display.reset(&mut MockDelay {}).unwrap();
+            display
+                .update(&[0x7F, 0xFF], &[0x00, 0x01], &mut MockDelay {})
+                .unwrap();
+        }
+
+        let frame = emulator.frame(&dimensions, Rotation::Rotate0);
+        assert_eq!((frame.width(), frame.height()), (8, 2));
+        assert_eq!(frame.pixel(0, 0), Color::Black);
+        assert_eq!(frame.pixel(7, 1), Color::Red);
+
+        let frame = emulator.frame(&dimensions, Rotation::Rotate90);
+        assert_eq!((frame.width(), frame.height()), (2, 8));
+        assert_eq!(frame.pixel(0, 7), Color::Black);
+        assert_eq!(frame.pixel(1, 0), Color::Red);
+
+        let frame = emulator.frame(&dimensions, Rotation::Rotate180);
+        assert_eq!(frame.pixel(7, 1), Color::Black);
+        assert_eq!(frame.pixel(0, 0), Color::Red);
+
+        let frame = emulator.frame(&dimensions, Rotation::Rotate270);
+        assert_eq!((frame.width(), frame.height()), (2, 8));
+        assert_eq!(frame.pixel(1, 0), Color::Black);
+        assert_eq!(frame.pixel(0, 7), Color::Red);
+        assert_eq!(frame.pixel(2, 0), Color::White);
+        assert_eq!(frame.pixel(0, 8), Color::White);
+    }
+
+    #[test]
+    fn ram_options_apply_when_latched() {
+        let mut emulator = Emulator::new();
+        BufCommand::WriteBlackData(&[0xF0])
+            .execute(&mut emulator)
+            .unwrap();
+        Command::XAddress(0).execute(&mut emulator).unwrap();
+        BufCommand::WriteRedData(&[0xFF])
+            .execute(&mut emulator)
+            .unwrap();
+        // Invert B/W RAM, bypass red RAM
+        DisplayInterface::send_command(&mut emulator, 0x21).unwrap();
+        DisplayInterface::send_data(&mut emulator, &[0x48]).unwrap();
+        Command::UpdateDisplay.execute(&mut emulator).unwrap();
+
+        assert_eq!(emulator.pixel(0, 0), Color::Black);
+        assert_eq!(emulator.pixel(4, 0), Color::White);
+        assert_eq!(emulator.black_ram()[0], 0xF0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn frame_export() {
+        let mut emulator = Emulator::new();
+        BufCommand::WriteBlackData(&[0x7F])
+            .execute(&mut emulator)
+            .unwrap();
+        Command::XAddress(0).execute(&mut emulator).unwrap();
+        BufCommand::WriteRedData(&[0x01])
+            .execute(&mut emulator)
+            .unwrap();
+        Command::UpdateDisplay.execute(&mut emulator).unwrap();
+        let frame = emulator.frame(&Dimensions { rows: 1, cols: 8 }, Rotation::Rotate0);
+
+        let mut ppm = Vec::new();
+        frame.write_ppm(&mut ppm).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n8 1\n255\n");
+        assert_eq!(&ppm[11..17], &[0, 0, 0, 255, 255, 255]);
+        assert_eq!(&ppm[ppm.len() - 3..], &[255, 0, 0]);
+
+        let mut encoded = Vec::new();
+        frame.write_png(&mut encoded).unwrap();
+        let decoder = png::Decoder::new(&encoded[..]);
+        let mut reader = decoder.read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(decoded, &ppm[11..]);
+    }
+
+    #[test]
+    fn read_ram() {
+        let mut emulator = Emulator::new();
+        BufCommand::WriteBlackData(&[0x12, 0x34])
+            .execute(&mut emulator)
+            .unwrap();
+        Command::XAddress(0).execute(&mut emulator).unwrap();
+        BufCommand::WriteRedData(&[0x56, 0x78])
+            .execute(&mut emulator)
+            .unwrap();
+
+        let mut buffer = [0xFF; 3];
+        Command::XAddress(0).execute(&mut emulator).unwrap();
+        Command::ReadData.read(&mut emulator, &mut buffer).unwrap();
+        assert_eq!(buffer, [0x00, 0x12, 0x34]);
+
+        Command::ReadRamOption(Ram::Red)
+            .execute(&mut emulator)
+            .unwrap();
+        Command::XAddress(0).execute(&mut emulator).unwrap();
+        Command::ReadData.read(&mut emulator, &mut buffer).unwrap();
+        assert_eq!(buffer, [0x00, 0x56, 0x78]);
+
+        let mut status = [0; 1];
+        Command::StatusBitRead
+            .read(&mut emulator, &mut status)
+            .unwrap();
+        assert_eq!(status, [CHIP_ID]);
+    }
+
+    #[test]
+    fn deep_sleep_ignores_commands_until_reset() {
+        let mut emulator = Emulator::new();
+        BufCommand::WriteBlackData(&[0x12])
+            .execute(&mut emulator)
+            .unwrap();
+        Command::DeepSleepMode(DeepSleepMode::PreserveRAM)
+            .execute(&mut emulator)
+            .unwrap();
+        assert!(emulator.is_sleeping());
+
+        Command::UpdateDisplay.execute(&mut emulator).unwrap();
+        assert_eq!(emulator.update_count(), 0);
+
+        DisplayInterface::reset(&mut emulator, &mut MockDelay {}).unwrap();
+        assert!(!emulator.is_sleeping());
+        assert_eq!(emulator.black_ram()[0], 0x12);
+
+        Command::DeepSleepMode(DeepSleepMode::DiscardRAM)
+            .execute(&mut emulator)
+            .unwrap();
+        assert_eq!(emulator.black_ram()[0], 0x00);
+    }
+}
@@ -0,0 +1,23 @@
+/// Error returned by the driver.
+///
+/// `SpiE` is the error type of the serial interface and `PinE` the error type shared by the GPIO
+/// pins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<SpiE, PinE> {
+    /// Writing to or reading from the serial interface failed
+    Spi(SpiE),
+    /// Setting the Data/Command pin failed
+    DcPin(PinE),
+    /// Setting the RESET pin failed
+    ResetPin(PinE),
+    /// Reading the BUSY pin failed
+    BusyPin(PinE),
+    /// The controller was still busy when the timeout elapsed
+    Timeout,
+    /// A buffer was too small for the display it was to be sent to
+    InvalidBufferLength,
+    /// A region was not byte aligned or did not fit within the display
+    InvalidRegion,
+    /// The controller RAM does not hold the last frame, so the buffers must be sent again
+    StaleRam,
+}
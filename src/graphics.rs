@@ -1,29 +1,46 @@
-use color::Color;
+#[cfg(feature = "async")]
+use crate::asynch::AsyncDisplayInterface;
+use crate::color::Color;
+#[cfg(any(feature = "graphics", feature = "emulator", test))]
+use crate::display::Rotation;
+use crate::display::{Display, Refresh, Region, SleepError, SleepMode, SleepingDisplay};
+use crate::error::Error;
+use crate::hal;
+use crate::interface::DisplayInterface;
 use core::{
     convert::AsMut,
     ops::{Deref, DerefMut},
 };
-use display::{Display, Rotation};
-use hal;
-use interface::DisplayInterface;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
 
 /// A display that holds buffers for drawing into and updating the display from.
 ///
 /// When the `graphics` feature is enabled `GraphicDisplay` implements the `Draw` trait from
 /// [embedded-graphics](https://crates.io/crates/embedded-graphics). This allows basic shapes and
 /// text to be drawn on the display.
-pub struct GraphicDisplay<'a, I, B = &'a mut [u8]>
-where
-    I: DisplayInterface,
-{
+pub struct GraphicDisplay<'a, I, B = &'a mut [u8]> {
     display: Display<'a, I>,
     black_buffer: B,
     red_buffer: B,
+    dirty: Option<Dirty>,
+}
+
+/// The bounding box of modified buffer bytes in native controller coordinates.
+#[derive(Clone, Copy)]
+struct Dirty {
+    /// First byte column
+    x_start: u8,
+    /// Last byte column (inclusive)
+    x_end: u8,
+    /// First row
+    y_start: u16,
+    /// Last row (inclusive)
+    y_end: u16,
 }
 
 impl<'a, I, B> GraphicDisplay<'a, I, B>
 where
-    I: DisplayInterface,
     B: AsMut<[u8]>,
 {
     /// Promote a `Display` to a `GraphicDisplay`.
@@ -35,20 +52,58 @@ where
             display,
             black_buffer,
             red_buffer,
+            dirty: None,
         }
     }
 
-    /// Update the display by writing the buffers to the controller.
-    pub fn update<D: hal::blocking::delay::DelayMs<u8>>(
-        &mut self,
-        delay: &mut D,
-    ) -> Result<(), I::Error> {
-        self.display
-            .update(self.black_buffer.as_mut(), self.red_buffer.as_mut(), delay)
+    /// Returns the region of the display that has been drawn to since the last update, if any.
+    ///
+    /// The region is in native controller coordinates, regardless of rotation, and is expanded to
+    /// whole bytes of controller RAM.
+    pub fn dirty_region(&self) -> Option<Region> {
+        self.dirty.map(|dirty| Region {
+            x: dirty.x_start * 8,
+            y: dirty.y_start,
+            cols: (dirty.x_end - dirty.x_start + 1) * 8,
+            rows: dirty.y_end - dirty.y_start + 1,
+        })
+    }
+
+    /// Include the byte at `index` in the buffers in the dirty region.
+    #[cfg(feature = "graphics")]
+    fn mark_dirty(&mut self, index: usize) {
+        let bytes_per_row = usize::from(self.cols() / 8);
+        let x = (index % bytes_per_row) as u8;
+        let y = (index / bytes_per_row) as u16;
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => Dirty {
+                x_start: dirty.x_start.min(x),
+                x_end: dirty.x_end.max(x),
+                y_start: dirty.y_start.min(y),
+                y_end: dirty.y_end.max(y),
+            },
+            None => Dirty {
+                x_start: x,
+                x_end: x,
+                y_start: y,
+                y_end: y,
+            },
+        });
     }
 
     /// Clear the buffers, filling them a single color.
     pub fn clear(&mut self, color: Color) {
+        self.fill_buffers(color);
+        self.dirty = Some(Dirty {
+            x_start: 0,
+            x_end: self.cols() / 8 - 1,
+            y_start: 0,
+            y_end: self.rows() - 1,
+        });
+    }
+
+    /// Fill the buffers with a single color without marking them dirty.
+    fn fill_buffers(&mut self, color: Color) {
         let (black, red) = match color {
             Color::White => (0xFF, 0x00),
             Color::Black => (0x00, 0x00),
@@ -65,6 +120,7 @@ where
         }
     }
 
+    #[cfg(feature = "graphics")]
     fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
         let (index, bit) = rotation(
             x,
@@ -74,6 +130,7 @@ where
             self.rotation(),
         );
         let index = index as usize;
+        self.mark_dirty(index);
 
         match color {
             Color::Black => {
@@ -92,27 +149,250 @@ where
     }
 }
 
-impl<'a, I, B> Deref for GraphicDisplay<'a, I, B>
+impl<'a, I, B> GraphicDisplay<'a, I, B>
 where
     I: DisplayInterface,
+    B: AsMut<[u8]>,
 {
-    type Target = Display<'a, I>;
+    /// Update the display by writing the buffers to the controller.
+    pub fn update<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.display
+            .update(self.black_buffer.as_mut(), self.red_buffer.as_mut(), delay)?;
+        self.dirty = None;
 
-    fn deref(&self) -> &Display<'a, I> {
-        &self.display
+        Ok(())
+    }
+
+    /// Start updating the display from the buffers without waiting for the refresh to finish.
+    ///
+    /// See `Display::start_update`.
+    pub fn start_update<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Refresh<'_, 'a, I>, Error<I::SpiError, I::PinError>> {
+        let refresh = self.display.start_update(
+            self.black_buffer.as_mut(),
+            self.red_buffer.as_mut(),
+            delay,
+        )?;
+        self.dirty = None;
+
+        Ok(refresh)
+    }
+
+    /// Update the display by writing only the parts of the buffers that have changed since the
+    /// last update.
+    ///
+    /// The bounding box of all pixels drawn since the last update is written to the controller
+    /// with `Display::update_region`. Does nothing if nothing has been drawn. If the controller
+    /// RAM no longer holds the last frame, for example after waking from
+    /// `SleepMode::DiscardRAM`, the whole buffers are written with `update` instead.
+    pub fn update_dirty<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        if !self.display.ram_valid() {
+            return self.update(delay);
+        }
+        if let Some(region) = self.dirty_region() {
+            self.display.update_region(
+                region,
+                self.black_buffer.as_mut(),
+                self.red_buffer.as_mut(),
+                delay,
+            )?;
+            self.dirty = None;
+        }
+
+        Ok(())
+    }
+
+    /// Clear the buffers and the controller RAM, filling them with a single color.
+    ///
+    /// The RAM is filled by the controller with `Display::clear_ram`, so nothing needs to be sent
+    /// by the next update. Follow this with `refresh_from_ram` to show the cleared display.
+    pub fn clear_ram<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        color: Color,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.fill_buffers(color);
+        self.display.clear_ram(color, delay)?;
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Enter deep sleep mode.
+    ///
+    /// The display is consumed and can only be used again after waking it with
+    /// `SleepingDisplay::wake`. The buffers are kept, so drawing can continue from where it left
+    /// off. See `Display::deep_sleep` for the modes. If the controller can't be put to sleep the
+    /// display is returned with the error.
+    #[allow(clippy::result_large_err)] // The display is returned with the error
+    pub fn deep_sleep<D: hal::blocking::delay::DelayMs<u8>>(
+        mut self,
+        mode: SleepMode,
+        delay: &mut D,
+    ) -> Result<SleepingDisplay<Self>, SleepError<Self, I::SpiError, I::PinError>> {
+        match self.display.enter_deep_sleep(mode, delay) {
+            Ok(()) => Ok(SleepingDisplay::new(self, mode)),
+            Err(error) => Err(SleepError {
+                display: self,
+                error,
+            }),
+        }
     }
 }
 
-impl<'a, I, B> DerefMut for GraphicDisplay<'a, I, B>
+impl<'a, I, B> SleepingDisplay<GraphicDisplay<'a, I, B>>
 where
     I: DisplayInterface,
+    B: AsMut<[u8]>,
+{
+    /// Wake the controller from deep sleep.
+    ///
+    /// The controller is reset and initialised, the same as `Display::reset`. If it can't be
+    /// woken the sleeping display is returned with the error.
+    // The display is returned with the error
+    #[allow(clippy::result_large_err, clippy::type_complexity)]
+    pub fn wake<D: hal::blocking::delay::DelayMs<u8>>(
+        self,
+        delay: &mut D,
+    ) -> Result<GraphicDisplay<'a, I, B>, SleepError<Self, I::SpiError, I::PinError>> {
+        let (mut display, mode) = self.into_inner();
+        match display.display.wake_from_sleep(mode, delay) {
+            Ok(()) => Ok(display),
+            Err(error) => Err(SleepError {
+                display: SleepingDisplay::new(display, mode),
+                error,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, I, B> GraphicDisplay<'a, I, B>
+where
+    I: AsyncDisplayInterface,
+    B: AsMut<[u8]>,
+{
+    /// Update the display by writing the buffers to the controller, without blocking.
+    ///
+    /// This is the async counterpart of `update`.
+    pub async fn update_async<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.display
+            .update_async(self.black_buffer.as_mut(), self.red_buffer.as_mut(), delay)
+            .await?;
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Update the display by writing only the parts of the buffers that have changed since the
+    /// last update, without blocking.
+    ///
+    /// This is the async counterpart of `update_dirty`.
+    pub async fn update_dirty_async<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        if !self.display.ram_valid() {
+            return self.update_async(delay).await;
+        }
+        if let Some(region) = self.dirty_region() {
+            self.display
+                .update_region_async(
+                    region,
+                    self.black_buffer.as_mut(),
+                    self.red_buffer.as_mut(),
+                    delay,
+                )
+                .await?;
+            self.dirty = None;
+        }
+
+        Ok(())
+    }
+
+    /// Clear the buffers and the controller RAM, filling them with a single color, without
+    /// blocking.
+    ///
+    /// This is the async counterpart of `clear_ram`.
+    pub async fn clear_ram_async(
+        &mut self,
+        color: Color,
+    ) -> Result<(), Error<I::SpiError, I::PinError>> {
+        self.fill_buffers(color);
+        self.display.clear_ram_async(color).await?;
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Enter deep sleep mode, without blocking.
+    ///
+    /// This is the async counterpart of `deep_sleep`.
+    pub async fn deep_sleep_async(
+        mut self,
+        mode: SleepMode,
+    ) -> Result<SleepingDisplay<Self>, SleepError<Self, I::SpiError, I::PinError>> {
+        match self.display.enter_deep_sleep_async(mode).await {
+            Ok(()) => Ok(SleepingDisplay::new(self, mode)),
+            Err(error) => Err(SleepError {
+                display: self,
+                error,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, I, B> SleepingDisplay<GraphicDisplay<'a, I, B>>
+where
+    I: AsyncDisplayInterface,
+    B: AsMut<[u8]>,
 {
+    /// Wake the controller from deep sleep, without blocking.
+    ///
+    /// This is the async counterpart of `wake`.
+    pub async fn wake_async<D: DelayNs>(
+        self,
+        delay: &mut D,
+    ) -> Result<GraphicDisplay<'a, I, B>, SleepError<Self, I::SpiError, I::PinError>> {
+        let (mut display, mode) = self.into_inner();
+        match display.display.wake_from_sleep_async(mode, delay).await {
+            Ok(()) => Ok(display),
+            Err(error) => Err(SleepError {
+                display: SleepingDisplay::new(display, mode),
+                error,
+            }),
+        }
+    }
+}
+
+impl<'a, I, B> Deref for GraphicDisplay<'a, I, B> {
+    type Target = Display<'a, I>;
+
+    fn deref(&self) -> &Display<'a, I> {
+        &self.display
+    }
+}
+
+impl<'a, I, B> DerefMut for GraphicDisplay<'a, I, B> {
     fn deref_mut(&mut self) -> &mut Display<'a, I> {
         &mut self.display
     }
 }
 
-fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32, u8) {
+#[cfg(any(feature = "graphics", feature = "emulator", test))]
+pub(crate) fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32, u8) {
     match rotation {
         Rotation::Rotate0 => (x / 8 + (width / 8) * y, 0x80 >> (x % 8)),
         Rotation::Rotate90 => ((width - 1 - y) / 8 + (width / 8) * x, 0x01 << (y % 8)),
@@ -132,7 +412,6 @@ use self::embedded_graphics::prelude::*;
 #[cfg(feature = "graphics")]
 impl<'a, I, B> DrawTarget for GraphicDisplay<'a, I, B>
 where
-    I: DisplayInterface,
     B: AsMut<[u8]>,
 {
     type Color = Color;
@@ -155,10 +434,7 @@ where
 }
 
 #[cfg(feature = "graphics")]
-impl<'a, I, B> OriginDimensions for GraphicDisplay<'a, I, B>
-where
-    I: DisplayInterface,
-{
+impl<'a, I, B> OriginDimensions for GraphicDisplay<'a, I, B> {
     fn size(&self) -> Size {
         match self.rotation() {
             Rotation::Rotate0 | Rotation::Rotate180 => {
@@ -175,13 +451,17 @@ where
 mod tests {
     use self::embedded_graphics::primitives::{PrimitiveStyleBuilder, Rectangle};
     use super::*;
-    use {Builder, Color, Dimensions, Display, DisplayInterface, GraphicDisplay, Rotation};
+    use crate::{
+        Builder, Color, Dimensions, Display, DisplayInterface, Error, GraphicDisplay, Region,
+        Rotation,
+    };
 
     const ROWS: u16 = 3;
     const COLS: u8 = 8;
     const BUFFER_SIZE: usize = (ROWS * COLS as u16) as usize / 8;
 
     struct MockInterface {}
+    #[derive(Debug)]
     struct MockError {}
 
     impl MockInterface {
@@ -191,19 +471,44 @@ mod tests {
     }
 
     impl DisplayInterface for MockInterface {
-        type Error = MockError;
+        type SpiError = MockError;
+        type PinError = MockError;
 
-        fn reset<D: hal::blocking::delay::DelayMs<u8>>(&mut self, _delay: &mut D) {}
+        fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+        ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+            Ok(())
+        }
+
+        fn send_command(
+            &mut self,
+            _command: u8,
+        ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+            Ok(())
+        }
 
-        fn send_command(&mut self, _command: u8) -> Result<(), Self::Error> {
+        fn send_data(&mut self, _data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
             Ok(())
         }
 
-        fn send_data(&mut self, _data: &[u8]) -> Result<(), Self::Error> {
+        fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+            &mut self,
+            _delay: &mut D,
+            _timeout_ms: u32,
+        ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
             Ok(())
         }
 
-        fn busy_wait(&self) {}
+        fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+            Ok(false)
+        }
+    }
+
+    struct MockDelay {}
+
+    impl hal::blocking::delay::DelayMs<u8> for MockDelay {
+        fn delay_ms(&mut self, _ms: u8) {}
     }
 
     fn build_mock_display<'a>() -> Display<'a, MockInterface> {
@@ -266,6 +571,66 @@ mod tests {
         assert_eq!(red_buffer, [0xFF, 0xFF, 0xFF]);
     }
 
+    #[test]
+    fn clear_ram() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut red_buffer = [0u8; BUFFER_SIZE];
+
+        {
+            let mut display =
+                GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut red_buffer);
+            display.clear_ram(Color::Red, &mut MockDelay {}).unwrap();
+            assert_eq!(display.dirty_region(), None);
+            assert!(display.ram_valid());
+        }
+
+        assert_eq!(black_buffer, [0xFF, 0xFF, 0xFF]);
+        assert_eq!(red_buffer, [0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn dirty_region() {
+        let mut black_buffer = [0u8; BUFFER_SIZE];
+        let mut red_buffer = [0u8; BUFFER_SIZE];
+        let mut display =
+            GraphicDisplay::new(build_mock_display(), &mut black_buffer, &mut red_buffer);
+        assert_eq!(display.dirty_region(), None);
+
+        display.clear(Color::White);
+        assert_eq!(
+            display.dirty_region(),
+            Some(Region {
+                x: 0,
+                y: 0,
+                cols: 8,
+                rows: 3
+            })
+        );
+
+        display.update_dirty(&mut MockDelay {}).unwrap();
+        assert_eq!(display.dirty_region(), None);
+
+        // Rotate270 maps x to native rows from the bottom up
+        Pixel(Point::new(0, 1), Color::Black)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(1, 6), Color::Red)
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(
+            display.dirty_region(),
+            Some(Region {
+                x: 0,
+                y: 1,
+                cols: 8,
+                rows: 2
+            })
+        );
+
+        display.update(&mut MockDelay {}).unwrap();
+        assert_eq!(display.dirty_region(), None);
+    }
+
     #[test]
     fn draw_rect_white() {
         let mut black_buffer = [0u8; BUFFER_SIZE];
@@ -0,0 +1,298 @@
+//! Support for embedded-hal 1.0.
+//!
+//! [SpiDeviceInterface] is built on the embedded-hal 1.0 `SpiDevice` trait, which manages chip
+//! select itself. This allows the SPI bus to be shared with other peripherals, for example with
+//! [embedded-hal-bus](https://crates.io/crates/embedded-hal-bus).
+//!
+//! The [Display](../display/struct.Display.html) methods take an embedded-hal 0.2 delay. Wrap an
+//! embedded-hal 1.0 `DelayNs` implementation in [Delay] to pass it to them.
+//!
+//! ### Example
+//!
+//! ```ignore
+//! use ssd1675::hal1::{Delay, SpiDeviceInterface};
+//! use ssd1675::{Builder, Dimensions, Display, Rotation};
+//!
+//! // `spi` implements `SpiDevice`, the pins `InputPin`/`OutputPin`, and `delay` `DelayNs`
+//! let controller = SpiDeviceInterface::new(spi, busy, dc, reset);
+//! let mut delay = Delay::new(delay);
+//!
+//! let config = Builder::new()
+//!     .dimensions(Dimensions { rows: 212, cols: 104 })
+//!     .rotation(Rotation::Rotate270)
+//!     .build()
+//!     .expect("invalid configuration");
+//! let mut display = Display::new(controller, config);
+//! display.reset(&mut delay).expect("error resetting display");
+//! ```
+//!
+//! [SpiDeviceInterface]: struct.SpiDeviceInterface.html
+//! [Delay]: struct.Delay.html
+
+use crate::hal;
+use embedded_hal_1::delay::DelayNs;
+use embedded_hal_1::digital::{InputPin, OutputPin};
+use embedded_hal_1::spi::SpiDevice;
+
+use crate::error::Error;
+use crate::interface::{poll_busy, DisplayInterface, ReadInterface, RESET_DELAY_MS};
+
+/// Adapts an embedded-hal 1.0 `DelayNs` implementation to the embedded-hal 0.2 `DelayMs<u8>`
+/// trait taken by the display.
+pub struct Delay<D> {
+    delay: D,
+}
+
+impl<D> Delay<D>
+where
+    D: DelayNs,
+{
+    /// Wrap an embedded-hal 1.0 delay.
+    pub fn new(delay: D) -> Self {
+        Self { delay }
+    }
+
+    /// Returns the wrapped delay.
+    pub fn into_inner(self) -> D {
+        self.delay
+    }
+}
+
+impl<D> hal::blocking::delay::DelayMs<u8> for Delay<D>
+where
+    D: DelayNs,
+{
+    fn delay_ms(&mut self, ms: u8) {
+        self.delay.delay_ms(u32::from(ms))
+    }
+}
+
+/// The hardware interface to a display using embedded-hal 1.0 traits.
+///
+/// Unlike [Interface](../interface/struct.Interface.html) there is no chip select pin, the
+/// `SpiDevice` asserts it for each transfer. The BUSY, DC, and RESET pins must share the same
+/// error type.
+pub struct SpiDeviceInterface<SPI, BUSY, DC, RESET> {
+    /// SPI device
+    spi: SPI,
+    /// Active low busy pin (input)
+    busy: BUSY,
+    /// Data/Command Control Pin (High for data, Low for command) (output)
+    dc: DC,
+    /// Pin for reseting the controller (output)
+    reset: RESET,
+}
+
+impl<SPI, BUSY, DC, RESET> SpiDeviceInterface<SPI, BUSY, DC, RESET>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RESET: OutputPin,
+{
+    /// Create a new SpiDeviceInterface from embedded-hal 1.0 traits.
+    pub fn new(spi: SPI, busy: BUSY, dc: DC, reset: RESET) -> Self {
+        Self {
+            spi,
+            busy,
+            dc,
+            reset,
+        }
+    }
+
+    /// Consume the interface, returning the SPI device and pins.
+    pub fn release(self) -> (SPI, BUSY, DC, RESET) {
+        (self.spi, self.busy, self.dc, self.reset)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
+        // Linux has a default limit of 4096 bytes per SPI transfer
+        // https://github.com/torvalds/linux/blob/ccda4af0f4b92f7b4c308d3acc262f4a7e3affad/drivers/spi/spidev.c#L93
+        if cfg!(target_os = "linux") {
+            for data_chunk in data.chunks(4096) {
+                self.spi.write(data_chunk)?;
+            }
+        } else {
+            self.spi.write(data)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<SPI, BUSY, DC, RESET, PinE> DisplayInterface for SpiDeviceInterface<SPI, BUSY, DC, RESET>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin<Error = PinE>,
+    DC: OutputPin<Error = PinE>,
+    RESET: OutputPin<Error = PinE>,
+{
+    type SpiError = SPI::Error;
+    type PinError = PinE;
+
+    fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.reset.set_low().map_err(Error::ResetPin)?;
+        delay.delay_ms(RESET_DELAY_MS);
+        self.reset.set_high().map_err(Error::ResetPin)?;
+        delay.delay_ms(RESET_DELAY_MS);
+
+        Ok(())
+    }
+
+    fn send_command(&mut self, command: u8) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_low().map_err(Error::DcPin)?;
+        self.write(&[command]).map_err(Error::Spi)?;
+        self.dc.set_high().map_err(Error::DcPin)?;
+
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_high().map_err(Error::DcPin)?;
+        self.write(data).map_err(Error::Spi)
+    }
+
+    fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        let busy = &mut self.busy;
+        poll_busy(|| busy.is_high(), delay, timeout_ms)
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+        self.busy.is_high().map_err(Error::BusyPin)
+    }
+}
+
+impl<SPI, BUSY, DC, RESET, PinE> ReadInterface for SpiDeviceInterface<SPI, BUSY, DC, RESET>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin<Error = PinE>,
+    DC: OutputPin<Error = PinE>,
+    RESET: OutputPin<Error = PinE>,
+{
+    fn read_data(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_high().map_err(Error::DcPin)?;
+
+        // Linux has a default limit of 4096 bytes per SPI transfer
+        if cfg!(target_os = "linux") {
+            for chunk in buffer.chunks_mut(4096) {
+                self.spi.read(chunk).map_err(Error::Spi)?;
+            }
+        } else {
+            self.spi.read(buffer).map_err(Error::Spi)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal_1::spi::{ErrorType, Operation};
+    use std::vec::Vec;
+
+    /// Records the bytes written in each transaction
+    #[derive(Default)]
+    struct MockSpi {
+        transactions: Vec<Vec<u8>>,
+    }
+
+    impl ErrorType for MockSpi {
+        type Error = Infallible;
+    }
+
+    impl SpiDevice for MockSpi {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+            let mut transaction = Vec::new();
+            for operation in operations {
+                match operation {
+                    Operation::Write(data) => transaction.extend_from_slice(data),
+                    Operation::Read(buffer) => {
+                        for byte in buffer.iter_mut() {
+                            *byte = 0xA5;
+                        }
+                    }
+                    _ => unimplemented!(),
+                }
+            }
+            self.transactions.push(transaction);
+            Ok(())
+        }
+    }
+
+    struct MockPin {
+        high: bool,
+    }
+
+    impl embedded_hal_1::digital::ErrorType for MockPin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for MockPin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.high = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    impl InputPin for MockPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.high)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.high)
+        }
+    }
+
+    struct MockDelay {
+        elapsed_ns: u32,
+    }
+
+    impl DelayNs for MockDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.elapsed_ns += ns;
+        }
+    }
+
+    #[test]
+    fn spi_device_interface() {
+        let mut interface = SpiDeviceInterface::new(
+            MockSpi::default(),
+            MockPin { high: false },
+            MockPin { high: false },
+            MockPin { high: false },
+        );
+        let mut delay = Delay::new(MockDelay { elapsed_ns: 0 });
+
+        interface.reset(&mut delay).unwrap();
+        interface.send_command(0x2F).unwrap();
+        interface.send_data(&[1, 2]).unwrap();
+        let mut buffer = [0; 2];
+        interface.read_data(&mut buffer).unwrap();
+        interface.busy_wait(&mut delay, 100).unwrap();
+
+        let (spi, _busy, dc, reset) = interface.release();
+        assert_eq!(spi.transactions, vec![vec![0x2F], vec![1, 2], vec![]]);
+        assert_eq!(buffer, [0xA5, 0xA5]);
+        assert!(dc.high);
+        assert!(reset.high);
+        assert_eq!(delay.into_inner().elapsed_ns, 20_000_000);
+    }
+}
@@ -1,27 +1,108 @@
-use core::fmt::Debug;
-use hal;
+use crate::hal;
+
+pub use crate::error::Error;
 
 // Section 15.2 of the HINK-E0213A07 data sheet says to hold for 10ms
-const RESET_DELAY_MS: u8 = 10;
+pub(crate) const RESET_DELAY_MS: u8 = 10;
+// Interval between checks of the BUSY pin
+const BUSY_POLL_MS: u8 = 1;
 
 /// Trait implemented by displays to provide implemenation of core functionality.
 pub trait DisplayInterface {
-    type Error;
+    /// Error type of the serial interface.
+    type SpiError;
+    /// Error type of the GPIO pins.
+    type PinError;
 
     /// Send a command to the controller.
     ///
     /// Prefer calling `execute` on a [Commmand](../command/enum.Command.html) over calling this
     /// directly.
-    fn send_command(&mut self, command: u8) -> Result<(), Self::Error>;
+    fn send_command(&mut self, command: u8) -> Result<(), Error<Self::SpiError, Self::PinError>>;
 
     /// Send data for a command.
-    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>>;
 
     /// Reset the controller.
-    fn reset<D: hal::blocking::delay::DelayMs<u8>>(&mut self, delay: &mut D);
+    fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>>;
 
     /// Wait for the controller to indicate it is not busy.
-    fn busy_wait(&self);
+    ///
+    /// Returns an error if the controller is still busy after `timeout_ms` milliseconds.
+    fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>>;
+
+    /// Returns whether the controller is busy, without waiting.
+    ///
+    /// This is used to poll a `display::Refresh`, so it must read the BUSY pin each time it's
+    /// called.
+    fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>>;
+}
+
+/// Trait implemented by displays that are able to read data back from the controller.
+///
+/// This requires the controller's SDA line to be readable by the host. With 4-wire SPI this means
+/// MISO must be connected, in 3-wire mode SDA is bidirectional.
+pub trait ReadInterface: DisplayInterface {
+    /// Read data in response to the command that was most recently sent.
+    ///
+    /// Prefer calling `read` on a [Commmand](../command/enum.Command.html) over calling this
+    /// directly.
+    fn read_data(&mut self, buffer: &mut [u8])
+        -> Result<(), Error<Self::SpiError, Self::PinError>>;
+}
+
+impl<I> DisplayInterface for &mut I
+where
+    I: DisplayInterface,
+{
+    type SpiError = I::SpiError;
+    type PinError = I::PinError;
+
+    fn send_command(&mut self, command: u8) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).send_command(command)
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).send_data(data)
+    }
+
+    fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).reset(delay)
+    }
+
+    fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).busy_wait(delay, timeout_ms)
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+        (**self).is_busy()
+    }
+}
+
+impl<I> ReadInterface for &mut I
+where
+    I: ReadInterface,
+{
+    fn read_data(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        (**self).read_data(buffer)
+    }
 }
 
 /// The hardware interface to a display.
@@ -79,7 +160,7 @@ pub trait DisplayInterface {
 ///
 /// // Build the interface from the pins and SPI device
 /// let controller = ssd1675::Interface::new(spi, cs, busy, dc, reset);
-
+/// ```
 #[allow(dead_code)] // Prevent warning about CS being unused
 pub struct Interface<SPI, CS, BUSY, DC, RESET> {
     /// SPI interface
@@ -134,43 +215,493 @@ where
     }
 }
 
-impl<SPI, CS, BUSY, DC, RESET> DisplayInterface for Interface<SPI, CS, BUSY, DC, RESET>
+impl<SPI, CS, BUSY, DC, RESET, PinE> DisplayInterface for Interface<SPI, CS, BUSY, DC, RESET>
 where
     SPI: hal::blocking::spi::Write<u8>,
     CS: hal::digital::v2::OutputPin,
-    CS::Error: Debug,
-    BUSY: hal::digital::v2::InputPin,
-    DC: hal::digital::v2::OutputPin,
-    DC::Error: Debug,
-    RESET: hal::digital::v2::OutputPin,
-    RESET::Error: Debug,
+    BUSY: hal::digital::v2::InputPin<Error = PinE>,
+    DC: hal::digital::v2::OutputPin<Error = PinE>,
+    RESET: hal::digital::v2::OutputPin<Error = PinE>,
 {
-    type Error = SPI::Error;
+    type SpiError = SPI::Error;
+    type PinError = PinE;
 
-    fn reset<D: hal::blocking::delay::DelayMs<u8>>(&mut self, delay: &mut D) {
-        self.reset.set_low().unwrap();
+    fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.reset.set_low().map_err(Error::ResetPin)?;
         delay.delay_ms(RESET_DELAY_MS);
-        self.reset.set_high().unwrap();
+        self.reset.set_high().map_err(Error::ResetPin)?;
         delay.delay_ms(RESET_DELAY_MS);
+
+        Ok(())
+    }
+
+    fn send_command(&mut self, command: u8) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_low().map_err(Error::DcPin)?;
+        self.write(&[command]).map_err(Error::Spi)?;
+        self.dc.set_high().map_err(Error::DcPin)?;
+
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_high().map_err(Error::DcPin)?;
+        self.write(data).map_err(Error::Spi)
+    }
+
+    fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        let busy = &self.busy;
+        poll_busy(|| busy.is_high(), delay, timeout_ms)
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+        self.busy.is_high().map_err(Error::BusyPin)
+    }
+}
+
+impl<SPI, CS, BUSY, DC, RESET, PinE> ReadInterface for Interface<SPI, CS, BUSY, DC, RESET>
+where
+    SPI: hal::blocking::spi::Write<u8>
+        + hal::blocking::spi::Transfer<u8, Error = <SPI as hal::blocking::spi::Write<u8>>::Error>,
+    CS: hal::digital::v2::OutputPin,
+    BUSY: hal::digital::v2::InputPin<Error = PinE>,
+    DC: hal::digital::v2::OutputPin<Error = PinE>,
+    RESET: hal::digital::v2::OutputPin<Error = PinE>,
+{
+    fn read_data(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.dc.set_high().map_err(Error::DcPin)?;
+
+        // The controller ignores MOSI while it is driving the response
+        for byte in buffer.iter_mut() {
+            *byte = 0;
+        }
+
+        // Linux has a default limit of 4096 bytes per SPI transfer
+        if cfg!(target_os = "linux") {
+            for chunk in buffer.chunks_mut(4096) {
+                self.spi.transfer(chunk).map_err(Error::Spi)?;
+            }
+        } else {
+            self.spi.transfer(buffer).map_err(Error::Spi)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A bit-banged hardware interface to a controller in 3-wire SPI mode.
+///
+/// When the BS1 pin of the controller is high it uses a 3-wire SPI interface. There is no
+/// data/command pin, instead each byte is preceded by a D/C bit (low for command, high for data),
+/// and the single SDA line is used for both writing to and reading from the controller.
+///
+/// Since SDA is bidirectional it must be configured as an open-drain output with a pull-up, so
+/// that setting it high releases the line and it can then be read. All pins must share the same
+/// error type.
+///
+/// Chip select is asserted when a command is sent and held so that the response to a read
+/// command can be clocked out. It's released once the data or response has been transferred, or
+/// when waiting for the controller to finish a command sent without data.
+///
+/// The SCL, SDA and CS pins form the serial interface, so their failures are reported as
+/// `Error::Spi`.
+pub struct ThreeWireInterface<SCL, SDA, CS, BUSY, RESET> {
+    /// Serial clock (output)
+    scl: SCL,
+    /// Serial data (open-drain output, read as input)
+    sda: SDA,
+    /// CS (chip select) (output)
+    cs: CS,
+    /// Active low busy pin (input)
+    busy: BUSY,
+    /// Pin for reseting the controller (output)
+    reset: RESET,
+}
+
+impl<SCL, SDA, CS, BUSY, RESET, E> ThreeWireInterface<SCL, SDA, CS, BUSY, RESET>
+where
+    SCL: hal::digital::v2::OutputPin<Error = E>,
+    SDA: hal::digital::v2::OutputPin<Error = E> + hal::digital::v2::InputPin<Error = E>,
+    CS: hal::digital::v2::OutputPin<Error = E>,
+    BUSY: hal::digital::v2::InputPin<Error = E>,
+    RESET: hal::digital::v2::OutputPin<Error = E>,
+{
+    /// Create a new ThreeWireInterface from embedded hal traits.
+    pub fn new(scl: SCL, sda: SDA, cs: CS, busy: BUSY, reset: RESET) -> Self {
+        Self {
+            scl,
+            sda,
+            cs,
+            busy,
+            reset,
+        }
+    }
+
+    /// Release chip select, ending the current transaction.
+    fn end_transaction(&mut self) -> Result<(), E> {
+        self.cs.set_high()
+    }
+
+    /// Write a 9-bit frame, D/C bit followed by `byte`, most significant bit first.
+    fn write_frame(&mut self, data: bool, byte: u8) -> Result<(), E> {
+        self.write_bit(data)?;
+        for bit in (0..8).rev() {
+            self.write_bit(byte & (1 << bit) != 0)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), E> {
+        if bit {
+            self.sda.set_high()?;
+        } else {
+            self.sda.set_low()?;
+        }
+        // The controller samples SDA on the rising edge of SCL
+        self.scl.set_high()?;
+        self.scl.set_low()
     }
 
-    fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
-        self.dc.set_low().unwrap();
-        self.write(&[command])?;
-        self.dc.set_high().unwrap();
+    /// Read a byte, clocking a high D/C bit first.
+    fn read_frame(&mut self) -> Result<u8, E> {
+        self.write_bit(true)?;
+
+        // Release SDA so the controller can drive it
+        self.sda.set_high()?;
+        let mut byte = 0;
+        for _ in 0..8 {
+            self.scl.set_high()?;
+            byte = (byte << 1) | self.sda.is_high()? as u8;
+            self.scl.set_low()?;
+        }
+
+        Ok(byte)
+    }
+}
+
+impl<SCL, SDA, CS, BUSY, RESET, E> DisplayInterface
+    for ThreeWireInterface<SCL, SDA, CS, BUSY, RESET>
+where
+    SCL: hal::digital::v2::OutputPin<Error = E>,
+    SDA: hal::digital::v2::OutputPin<Error = E> + hal::digital::v2::InputPin<Error = E>,
+    CS: hal::digital::v2::OutputPin<Error = E>,
+    BUSY: hal::digital::v2::InputPin<Error = E>,
+    RESET: hal::digital::v2::OutputPin<Error = E>,
+{
+    type SpiError = E;
+    type PinError = E;
+
+    fn reset<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        self.reset.set_low().map_err(Error::ResetPin)?;
+        delay.delay_ms(RESET_DELAY_MS);
+        self.reset.set_high().map_err(Error::ResetPin)?;
+        delay.delay_ms(RESET_DELAY_MS);
 
         Ok(())
     }
 
-    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
-        self.dc.set_high().unwrap();
-        self.write(data)
+    fn send_command(&mut self, command: u8) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        // End the previous transaction and start a new one
+        self.cs.set_high().map_err(Error::Spi)?;
+        self.scl.set_low().map_err(Error::Spi)?;
+        self.cs.set_low().map_err(Error::Spi)?;
+        self.write_frame(false, command).map_err(Error::Spi)
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        for byte in data {
+            self.write_frame(true, *byte).map_err(Error::Spi)?;
+        }
+
+        self.end_transaction().map_err(Error::Spi)
+    }
+
+    fn busy_wait<D: hal::blocking::delay::DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        // No transaction spans a wait for BUSY
+        self.end_transaction().map_err(Error::Spi)?;
+        let busy = &self.busy;
+        poll_busy(|| busy.is_high(), delay, timeout_ms)
+    }
+
+    fn is_busy(&mut self) -> Result<bool, Error<Self::SpiError, Self::PinError>> {
+        self.end_transaction().map_err(Error::Spi)?;
+        self.busy.is_high().map_err(Error::BusyPin)
+    }
+}
+
+impl<SCL, SDA, CS, BUSY, RESET, E> ReadInterface for ThreeWireInterface<SCL, SDA, CS, BUSY, RESET>
+where
+    SCL: hal::digital::v2::OutputPin<Error = E>,
+    SDA: hal::digital::v2::OutputPin<Error = E> + hal::digital::v2::InputPin<Error = E>,
+    CS: hal::digital::v2::OutputPin<Error = E>,
+    BUSY: hal::digital::v2::InputPin<Error = E>,
+    RESET: hal::digital::v2::OutputPin<Error = E>,
+{
+    fn read_data(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<Self::SpiError, Self::PinError>> {
+        for byte in buffer.iter_mut() {
+            *byte = self.read_frame().map_err(Error::Spi)?;
+        }
+
+        self.end_transaction().map_err(Error::Spi)
+    }
+}
+
+/// Poll `is_busy` until it returns false, or `timeout_ms` elapses.
+pub(crate) fn poll_busy<F, D, SpiE, PinE>(
+    mut is_busy: F,
+    delay: &mut D,
+    timeout_ms: u32,
+) -> Result<(), Error<SpiE, PinE>>
+where
+    F: FnMut() -> Result<bool, PinE>,
+    D: hal::blocking::delay::DelayMs<u8>,
+{
+    let mut waited_ms = 0;
+    while is_busy().map_err(Error::BusyPin)? {
+        if waited_ms >= timeout_ms {
+            return Err(Error::Timeout);
+        }
+        delay.delay_ms(BUSY_POLL_MS);
+        waited_ms += u32::from(BUSY_POLL_MS);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use std::vec::Vec;
+
+    /// State of the 3-wire bus shared between the mock pins
+    #[derive(Default)]
+    struct Bus {
+        sda: bool,
+        scl: bool,
+        cs: bool,
+        /// Value of SDA sampled on each rising edge of SCL
+        clocked: Vec<bool>,
+        /// Bits driven by the controller when SDA is read
+        response: Vec<bool>,
+    }
+
+    struct Pin<'a> {
+        bus: &'a RefCell<Bus>,
+        kind: PinKind,
+    }
+
+    #[derive(PartialEq)]
+    enum PinKind {
+        Scl,
+        Sda,
+        Cs,
+        Other,
+    }
+
+    impl<'a> hal::digital::v2::OutputPin for Pin<'a> {
+        type Error = ();
+
+        fn set_low(&mut self) -> Result<(), ()> {
+            let mut bus = self.bus.borrow_mut();
+            match self.kind {
+                PinKind::Scl => bus.scl = false,
+                PinKind::Sda => bus.sda = false,
+                PinKind::Cs => bus.cs = false,
+                PinKind::Other => {}
+            }
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), ()> {
+            let mut bus = self.bus.borrow_mut();
+            match self.kind {
+                PinKind::Scl if !bus.scl => {
+                    bus.scl = true;
+                    let sda = bus.sda;
+                    bus.clocked.push(sda);
+                }
+                PinKind::Sda => bus.sda = true,
+                PinKind::Cs => bus.cs = true,
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    impl<'a> hal::digital::v2::InputPin for Pin<'a> {
+        type Error = ();
+
+        fn is_high(&self) -> Result<bool, ()> {
+            let mut bus = self.bus.borrow_mut();
+            if self.kind == PinKind::Sda && !bus.response.is_empty() {
+                Ok(bus.response.remove(0))
+            } else {
+                Ok(false)
+            }
+        }
+
+        fn is_low(&self) -> Result<bool, ()> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    fn bits(data: bool, byte: u8) -> Vec<bool> {
+        let mut bits = vec![data];
+        bits.extend((0..8).rev().map(|bit| byte & (1 << bit) != 0));
+        bits
+    }
+
+    struct MockDelay {
+        elapsed_ms: u32,
+    }
+
+    impl hal::blocking::delay::DelayMs<u8> for MockDelay {
+        fn delay_ms(&mut self, ms: u8) {
+            self.elapsed_ms += u32::from(ms);
+        }
+    }
+
+    #[test]
+    fn busy_wait_timeout() {
+        let mut delay = MockDelay { elapsed_ms: 0 };
+        let result: Result<(), Error<(), ()>> = poll_busy(|| Ok(true), &mut delay, 100);
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert_eq!(delay.elapsed_ms, 100);
+
+        let mut polls = 0;
+        let mut delay = MockDelay { elapsed_ms: 0 };
+        let result: Result<(), Error<(), ()>> = poll_busy(
+            || {
+                polls += 1;
+                Ok(polls < 5)
+            },
+            &mut delay,
+            100,
+        );
+        assert!(result.is_ok());
+        assert_eq!(delay.elapsed_ms, 4);
+
+        let result: Result<(), Error<(), ()>> = poll_busy(|| Err(()), &mut delay, 100);
+        assert!(matches!(result, Err(Error::BusyPin(()))));
+    }
+
+    #[test]
+    fn three_wire_frames() {
+        let bus = RefCell::new(Bus::default());
+        let pin = |kind| Pin { bus: &bus, kind };
+        let mut interface = ThreeWireInterface::new(
+            pin(PinKind::Scl),
+            pin(PinKind::Sda),
+            pin(PinKind::Cs),
+            pin(PinKind::Other),
+            pin(PinKind::Other),
+        );
+
+        interface.send_command(0x22).unwrap();
+        assert!(!bus.borrow().cs);
+        interface.send_data(&[0xA5]).unwrap();
+        assert!(bus.borrow().cs);
+
+        // Chip select is held from a read command until the response has been read
+        interface.send_command(0x2F).unwrap();
+        assert!(!bus.borrow().cs);
+        bus.borrow_mut().response = bits(false, 0x3C)[1..].to_vec();
+        let mut buffer = [0; 1];
+        interface.read_data(&mut buffer).unwrap();
+        assert!(bus.borrow().cs);
+
+        let mut expected = bits(false, 0x22);
+        expected.extend(bits(true, 0xA5));
+        expected.extend(bits(false, 0x2F));
+        // D/C bit, then SDA released while the controller drives it
+        expected.extend(bits(true, 0xFF));
+        assert_eq!(bus.borrow().clocked, expected);
+        assert_eq!(buffer, [0x3C]);
+
+        // and released when waiting for a command without data to finish
+        interface.send_command(0x20).unwrap();
+        assert!(!bus.borrow().cs);
+        interface
+            .busy_wait(&mut MockDelay { elapsed_ms: 0 }, 100)
+            .unwrap();
+        assert!(bus.borrow().cs);
+    }
+
+    struct MockSpi {}
+
+    impl hal::blocking::spi::Write<u8> for MockSpi {
+        type Error = ();
+
+        fn write(&mut self, _words: &[u8]) -> Result<(), ()> {
+            Ok(())
+        }
     }
 
-    fn busy_wait(&self) {
-        while match self.busy.is_high() {
-            Ok(x) => x,
-            _ => false,
-        } {}
+    /// A pin that fails to be set
+    struct FailingPin {}
+
+    impl hal::digital::v2::OutputPin for FailingPin {
+        type Error = ();
+
+        fn set_low(&mut self) -> Result<(), ()> {
+            Err(())
+        }
+
+        fn set_high(&mut self) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    impl hal::digital::v2::InputPin for FailingPin {
+        type Error = ();
+
+        fn is_high(&self) -> Result<bool, ()> {
+            Err(())
+        }
+
+        fn is_low(&self) -> Result<bool, ()> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn pin_errors() {
+        let mut interface = Interface::new(
+            MockSpi {},
+            FailingPin {},
+            FailingPin {},
+            FailingPin {},
+            FailingPin {},
+        );
+        let mut delay = MockDelay { elapsed_ms: 0 };
+
+        assert_eq!(interface.send_command(0x12), Err(Error::DcPin(())));
+        assert_eq!(interface.send_data(&[0]), Err(Error::DcPin(())));
+        assert_eq!(interface.reset(&mut delay), Err(Error::ResetPin(())));
+        assert_eq!(
+            interface.busy_wait(&mut delay, 100),
+            Err(Error::BusyPin(()))
+        );
     }
 }
@@ -29,30 +29,67 @@
 //! 1. [clear](graphics/struct.GraphicDisplay.html#method.clear)
 //! 1. [update](graphics/struct.GraphicDisplay.html#method.update)
 //! 1. [sleep](display/struct.Display.html#method.deep_sleep)
+//! 1. [wake](display/struct.SleepingDisplay.html#method.wake), then continue from clear
+//!
+//! With the `embedded-hal-1` feature enabled a [SpiDeviceInterface] built on embedded-hal 1.0 is
+//! also available. It leaves chip select to the `SpiDevice`, so the bus can be shared.
+//!
+//! With the `async` feature enabled an [AsyncInterface] built on embedded-hal-async is available,
+//! along with async methods on [Display] and [GraphicDisplay], such as `update_async`. These await
+//! the BUSY pin rather than blocking while the panel refreshes.
+//!
+//! To test code that drives a display without any hardware attached, enable the `emulator`
+//! feature and use an [Emulator](emulator/struct.Emulator.html) in place of an [Interface].
+//!
+//! Rather than packing the 70 byte lookup table by hand, it can be built from a typed
+//! [Waveform](waveform/struct.Waveform.html).
 //!
 //! [Interface]: interface/struct.Interface.html
 //! [Display]: display/struct.Display.html
+//! [AsyncInterface]: asynch/struct.AsyncInterface.html
+//! [SpiDeviceInterface]: hal1/struct.SpiDeviceInterface.html
 //! [GraphicDisplay]: display/struct.GraphicDisplay.html
 //! [Config]: config/struct.Config.html
 //! [Builder]: config/struct.Builder.html
 //! [embedded-graphics]: https://crates.io/crates/embedded-graphics
 
 extern crate embedded_hal as hal;
+#[cfg(feature = "embedded-hal-1")]
+extern crate embedded_hal_1;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 #[macro_use]
 extern crate std;
 
+#[cfg(feature = "async")]
+pub mod asynch;
 mod color;
 pub mod command;
 pub mod config;
 pub mod display;
+#[cfg(any(test, feature = "emulator"))]
+pub mod emulator;
+mod error;
 pub mod graphics;
+#[cfg(feature = "embedded-hal-1")]
+pub mod hal1;
 pub mod interface;
+pub mod waveform;
 
-pub use color::Color;
-pub use config::Builder;
-pub use display::{Dimensions, Display, Rotation};
-pub use graphics::GraphicDisplay;
-pub use interface::DisplayInterface;
-pub use interface::Interface;
+#[cfg(feature = "async")]
+pub use crate::asynch::{AsyncDisplayInterface, AsyncInterface};
+pub use crate::color::Color;
+pub use crate::config::Builder;
+pub use crate::display::{
+    Dimensions, Display, Pattern, Refresh, Region, Rotation, SleepError, SleepMode,
+    SleepingDisplay, Step,
+};
+pub use crate::error::Error;
+pub use crate::graphics::GraphicDisplay;
+#[cfg(feature = "embedded-hal-1")]
+pub use crate::hal1::SpiDeviceInterface;
+pub use crate::interface::DisplayInterface;
+pub use crate::interface::Interface;
+pub use crate::interface::ReadInterface;
+pub use crate::interface::ThreeWireInterface;
+pub use crate::waveform::Waveform;
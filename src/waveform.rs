@@ -0,0 +1,357 @@
+//! Typed waveform lookup tables.
+//!
+//! The lookup table (LUT) written with command 0x32 describes the voltages the controller drives
+//! during an update. It is 70 bytes: five voltage select rows of seven bytes, one per phase,
+//! followed by seven five byte entries giving the duration of each phase and how many times it
+//! repeats.
+//!
+//! Each phase is made up of four sub-phases, A, B, C, and D. A pixel is driven with the row
+//! selected by its bits in the B/W and Red RAM, while the VCOM row drives the common electrode.
+//! [Waveform] models this so a LUT can be built without packing bits by hand, and existing LUTs
+//! can be parsed to inspect or tweak them.
+//!
+//! ### Example
+//!
+//! ```
+//! use ssd1675::waveform::{Phase, Voltage, Waveform};
+//! use ssd1675::Builder;
+//!
+//! const WAVEFORM: Waveform = Waveform::new()
+//!     .phase(
+//!         0,
+//!         Phase {
+//!             black: [Voltage::Vsl, Voltage::Vss, Voltage::Vss, Voltage::Vss],
+//!             white: [Voltage::Vsh1, Voltage::Vss, Voltage::Vss, Voltage::Vss],
+//!             red: [Voltage::Vsh2, Voltage::Vss, Voltage::Vss, Voltage::Vss],
+//!             durations: [32, 0, 0, 0],
+//!             ..Phase::EMPTY
+//!         },
+//!     );
+//! const LUT: [u8; 70] = WAVEFORM.to_bytes();
+//!
+//! let builder = Builder::new().lut(&LUT);
+//! assert_eq!(Waveform::from_bytes(&LUT), Ok(WAVEFORM));
+//! ```
+//!
+//! [Waveform]: struct.Waveform.html
+
+use core::time::Duration;
+
+/// The number of phases in a waveform.
+pub const PHASES: usize = 7;
+/// The length of a serialised waveform in bytes.
+pub const LUT_LEN: usize = 70;
+
+/// The number of voltage select rows.
+const ROWS: usize = 5;
+/// The offset of the duration and repeat table.
+const TIMING_OFFSET: usize = ROWS * PHASES;
+/// The number of bytes in each entry of the duration and repeat table.
+const TIMING_LEN: usize = 5;
+
+/// The voltage driven during a sub-phase.
+///
+/// For the VCOM row the levels are offset by the VCOM DC level (see `Builder::vcom`) and
+/// `Vsh2` leaves VCOM floating.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Voltage {
+    /// Ground, or VCOM DC for the VCOM row.
+    #[default]
+    Vss = 0b00,
+    /// The first positive source voltage, VSH1.
+    Vsh1 = 0b01,
+    /// The negative source voltage, VSL.
+    Vsl = 0b10,
+    /// The second positive source voltage, VSH2. Floating for the VCOM row.
+    Vsh2 = 0b11,
+}
+
+impl Voltage {
+    pub(crate) const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Voltage::Vss,
+            0b01 => Voltage::Vsh1,
+            0b10 => Voltage::Vsl,
+            _ => Voltage::Vsh2,
+        }
+    }
+}
+
+/// The voltages driven in sub-phases A, B, C, and D of a phase.
+pub type PhaseVoltages = [Voltage; 4];
+
+/// A single phase of a waveform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Phase {
+    /// LUT0, used for pixels that are 0 in both B/W and Red RAM (black).
+    pub black: PhaseVoltages,
+    /// LUT1, used for pixels that are 1 in B/W RAM and 0 in Red RAM (white).
+    pub white: PhaseVoltages,
+    /// LUT2, used for pixels that are 0 in B/W RAM and 1 in Red RAM. This crate does not produce
+    /// this combination, red pixels are written as 1 in both.
+    pub red_alt: PhaseVoltages,
+    /// LUT3, used for pixels that are 1 in both B/W and Red RAM (red).
+    pub red: PhaseVoltages,
+    /// LUT4, driven on the common electrode.
+    pub vcom: PhaseVoltages,
+    /// The duration of sub-phases A, B, C, and D in frames. A duration of 0 skips the sub-phase.
+    pub durations: [u8; 4],
+    /// The number of times the phase is repeated after it first runs, RP\[n\] in the datasheet.
+    /// A repeat count of 0 runs the phase once.
+    pub repeat: u8,
+}
+
+impl Phase {
+    /// A phase that drives nothing and takes no time.
+    pub const EMPTY: Phase = Phase {
+        black: [Voltage::Vss; 4],
+        white: [Voltage::Vss; 4],
+        red_alt: [Voltage::Vss; 4],
+        red: [Voltage::Vss; 4],
+        vcom: [Voltage::Vss; 4],
+        durations: [0; 4],
+        repeat: 0,
+    };
+
+    /// The number of frames the phase takes, including repeats.
+    ///
+    /// The controller runs the phase `repeat + 1` times.
+    pub const fn frames(&self) -> u32 {
+        let [a, b, c, d] = self.durations;
+        (a as u32 + b as u32 + c as u32 + d as u32) * (self.repeat as u32 + 1)
+    }
+
+    /// The voltage select rows in LUT order.
+    const fn rows(&self) -> [PhaseVoltages; ROWS] {
+        [self.black, self.white, self.red_alt, self.red, self.vcom]
+    }
+}
+
+/// Error returned if a waveform can not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaveformError {
+    /// The LUT was not exactly 70 bytes long. Contains the length supplied.
+    InvalidLength(usize),
+}
+
+/// A typed waveform lookup table.
+///
+/// Use `to_bytes` to obtain the 70 byte LUT to pass to `Builder::lut`. Since it is a `const fn`
+/// the LUT can be built at compile time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Waveform {
+    /// The phases, in the order they are run.
+    pub phases: [Phase; PHASES],
+}
+
+impl Waveform {
+    /// Create a waveform where every phase is empty.
+    pub const fn new() -> Self {
+        Waveform {
+            phases: [Phase::EMPTY; PHASES],
+        }
+    }
+
+    /// Set phase `index` of the waveform.
+    ///
+    /// Panics if `index` is not less than `PHASES`.
+    pub const fn phase(mut self, index: usize, phase: Phase) -> Self {
+        assert!(index < PHASES, "phase index must be less than PHASES");
+        self.phases[index] = phase;
+        self
+    }
+
+    /// The number of frames the waveform takes.
+    pub const fn frames(&self) -> u32 {
+        let mut frames = 0;
+        let mut index = 0;
+        while index < PHASES {
+            frames += self.phases[index].frames();
+            index += 1;
+        }
+
+        frames
+    }
+
+    /// Estimate how long an update with this waveform takes, given the time taken by each frame.
+    ///
+    /// The frame period depends on the display configuration, see `Config::frame_period`.
+    pub fn refresh_duration(&self, frame_period: Duration) -> Duration {
+        frame_period * self.frames()
+    }
+
+    /// Parse a 70 byte LUT, such as one supplied by a panel manufacturer.
+    ///
+    /// Returns `WaveformError::InvalidLength` if `lut` is not exactly 70 bytes.
+    pub fn from_bytes(lut: &[u8]) -> Result<Self, WaveformError> {
+        if lut.len() != LUT_LEN {
+            return Err(WaveformError::InvalidLength(lut.len()));
+        }
+
+        let mut waveform = Waveform::new();
+        for (index, phase) in waveform.phases.iter_mut().enumerate() {
+            let levels = |row: usize| {
+                let byte = lut[row * PHASES + index];
+                [
+                    Voltage::from_bits(byte >> 6),
+                    Voltage::from_bits(byte >> 4),
+                    Voltage::from_bits(byte >> 2),
+                    Voltage::from_bits(byte),
+                ]
+            };
+            let timing = &lut[TIMING_OFFSET + index * TIMING_LEN..][..TIMING_LEN];
+            *phase = Phase {
+                black: levels(0),
+                white: levels(1),
+                red_alt: levels(2),
+                red: levels(3),
+                vcom: levels(4),
+                durations: [timing[0], timing[1], timing[2], timing[3]],
+                repeat: timing[4],
+            };
+        }
+
+        Ok(waveform)
+    }
+
+    /// Serialise the waveform to the 70 byte layout expected by command 0x32.
+    pub const fn to_bytes(&self) -> [u8; LUT_LEN] {
+        let mut lut = [0; LUT_LEN];
+
+        let mut index = 0;
+        while index < PHASES {
+            let phase = &self.phases[index];
+
+            let rows = phase.rows();
+            let mut row = 0;
+            while row < ROWS {
+                let [a, b, c, d] = rows[row];
+                lut[row * PHASES + index] =
+                    (a as u8) << 6 | (b as u8) << 4 | (c as u8) << 2 | d as u8;
+                row += 1;
+            }
+
+            let timing = TIMING_OFFSET + index * TIMING_LEN;
+            lut[timing] = phase.durations[0];
+            lut[timing + 1] = phase.durations[1];
+            lut[timing + 2] = phase.durations[2];
+            lut[timing + 3] = phase.durations[3];
+            lut[timing + 4] = phase.repeat;
+
+            index += 1;
+        }
+
+        lut
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Dimensions};
+
+    #[rustfmt::skip]
+    const LUT: [u8; 70] = [
+        0b01001000, 0b10100000, 0b00010000, 0b00010000, 0b00010011, 0b00000000, 0b00000000,
+        0b01001000, 0b10100000, 0b10000000, 0b00000000, 0b00000011, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+        0b01001000, 0b10100101, 0b00000000, 0b10111011, 0b00000000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+        64, 12, 32, 12, 6,
+        16, 8, 4, 4, 6,
+        4, 8, 8, 16, 16,
+        2, 2, 2, 64, 32,
+        2, 2, 2, 2, 2,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn parse() {
+        let waveform = Waveform::from_bytes(&LUT).unwrap();
+        assert_eq!(
+            waveform.phases[0],
+            Phase {
+                black: [Voltage::Vsh1, Voltage::Vss, Voltage::Vsl, Voltage::Vss],
+                white: [Voltage::Vsh1, Voltage::Vss, Voltage::Vsl, Voltage::Vss],
+                red: [Voltage::Vsh1, Voltage::Vss, Voltage::Vsl, Voltage::Vss],
+                durations: [64, 12, 32, 12],
+                repeat: 6,
+                ..Phase::EMPTY
+            }
+        );
+        assert_eq!(
+            waveform.phases[3].red,
+            [Voltage::Vsl, Voltage::Vsh2, Voltage::Vsl, Voltage::Vsh2]
+        );
+        assert_eq!(waveform.phases[6], Phase::EMPTY);
+    }
+
+    #[test]
+    fn round_trip() {
+        let waveform = Waveform::from_bytes(&LUT).unwrap();
+        assert_eq!(waveform.to_bytes(), LUT);
+
+        const BUILT: [u8; LUT_LEN] = Waveform::new()
+            .phase(
+                6,
+                Phase {
+                    vcom: [Voltage::Vss, Voltage::Vsh1, Voltage::Vsl, Voltage::Vsh2],
+                    durations: [1, 2, 3, 4],
+                    repeat: 5,
+                    ..Phase::EMPTY
+                },
+            )
+            .to_bytes();
+        assert_eq!(BUILT[4 * PHASES + 6], 0b00_01_10_11);
+        assert_eq!(BUILT[TIMING_OFFSET + 6 * TIMING_LEN..], [1, 2, 3, 4, 5]);
+        assert_eq!(Waveform::from_bytes(&BUILT).unwrap().to_bytes(), BUILT);
+    }
+
+    #[test]
+    fn frames() {
+        let waveform = Waveform::from_bytes(&LUT).unwrap();
+        assert_eq!(waveform.phases[0].frames(), 120 * 7);
+        assert_eq!(waveform.phases[6].frames(), 0);
+        assert_eq!(waveform.frames(), 840 + 224 + 612 + 2310 + 24);
+        assert_eq!(
+            waveform.refresh_duration(Duration::from_millis(10)),
+            Duration::from_millis(40_100)
+        );
+    }
+
+    #[test]
+    fn inky_phat_refresh_duration() {
+        // The LUT and dimensions used by the Raspberry Pi Inky pHAT example
+        let config = Builder::new()
+            .dimensions(Dimensions {
+                rows: 212,
+                cols: 104,
+            })
+            .lut(&LUT)
+            .build()
+            .unwrap();
+        // 4010 frames of (212 + 7) lines of 44µs each
+        assert_eq!(
+            config.refresh_duration(),
+            Some(Duration::from_micros(38_640_360))
+        );
+        // Longer than the default busy timeout, so the example sets its own
+        assert!(config.refresh_duration().unwrap() > Duration::from_millis(30_000));
+        assert!(config.refresh_duration().unwrap() < Duration::from_millis(60_000));
+    }
+
+    #[test]
+    fn invalid_length() {
+        assert_eq!(
+            Waveform::from_bytes(&LUT[..69]),
+            Err(WaveformError::InvalidLength(69))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn phase_out_of_range() {
+        let _ = Waveform::new().phase(PHASES, Phase::EMPTY);
+    }
+}